@@ -0,0 +1,20 @@
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+
+pub mod nonogram;
+mod priority_queue;
+
+pub use nonogram::{
+    check::LineViolation,
+    solution::{Cell, Solution},
+    solve::{
+        check_uniqueness, count_solutions, solutions, solve, solve_with, BranchCandidate,
+        BranchHeuristic, CancellationToken, ChangedCellsPriority, FifoPriority,
+        FirstUndeterminedHeuristic, FreeSpacePriority, InitialLine, LineChange, LinePriority,
+        MaxEntropyHeuristic, MostConstrainedHeuristic, ProbabilityHeuristic, ProbabilityPriority,
+        RandomHeuristic, SolutionCount, Solutions, SolveOptions, SolveOutput, SolveResult,
+        SolveStats, Uniqueness,
+    },
+    validation::PuzzleError,
+    Description, LineId, Puzzle,
+};
@@ -1,24 +1,491 @@
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 
-use anyhow::Result;
-use nonogram::{solve::solve, Puzzle};
-use std::io::stdin;
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use nonogram_solver::{
+    nonogram::{
+        batch::{puzzle_paths, solve_batch, write_csv_report, write_json_report},
+        format::Format as PuzzleFormat,
+        render::grid::Undetermined,
+    },
+    BranchHeuristic, ChangedCellsPriority, FifoPriority, FirstUndeterminedHeuristic,
+    FreeSpacePriority, LinePriority, MaxEntropyHeuristic, MostConstrainedHeuristic,
+    ProbabilityHeuristic, ProbabilityPriority, Puzzle, RandomHeuristic, SolveOptions, SolveOutput,
+    SolveResult, SolveStats,
+};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{stdin, stdout, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
 
-mod nonogram;
-mod priority_queue;
+/// Solves nonograms given as JSON, webpbn XML, `.non`, `.g`, `.nin` or CWD files.
+///
+/// The exit code is 0 if every puzzle was fully solved, 1 if some puzzle has several solutions,
+/// 2 if some puzzle has no solution, 3 if some input could not be read, 4 on a usage error,
+/// 5 if some puzzle was not solved into the goal given by its file
+/// and 6 if some solve timed out or exhausted its budget.
+///
+/// When the inputs have different outcomes, the most severe one gives the exit code,
+/// from the most severe: 4, 3, 6, 5, 2, 1 and 0.
+#[derive(Debug, Parser)]
+#[allow(clippy::struct_excessive_bools)]
+#[command(version)]
+struct Cli {
+    /// Puzzle files or directories of puzzle files to solve, or `-` for the standard input
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
 
-fn main() -> Result<()> {
-    println!("Type a puzzle name.");
-    let mut puzzle_name = String::new();
-    stdin()
-        .read_line(&mut puzzle_name)
-        .expect("Faild to read line.");
-    let puzzle_name = puzzle_name.trim();
+    /// Format of the solutions
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    format: Format,
 
-    let puzzle = Puzzle::from_json(puzzle_name)?;
+    /// Print the clues beside the grid with `--format ansi`
+    #[arg(long)]
+    clues: bool,
 
-    solve(&puzzle);
+    /// Write the undetermined pixels as `null` with `--format grid`, instead of their candidates
+    #[arg(long)]
+    null_undetermined: bool,
+
+    /// Write the solutions into this file instead of the standard output
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Print the statistics of each solve to the standard error
+    #[arg(long, value_enum)]
+    stats: Option<StatsFormat>,
+
+    /// Do not print the solutions
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Write a report of every puzzle into this file, as JSON if it ends with `.json` and as CSV otherwise
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Solve the puzzles in parallel
+    #[arg(short = 'j', long)]
+    parallel: bool,
+
+    /// Give up each puzzle after this many seconds, printing the pixels deduced so far
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    timeout: Option<Duration>,
+
+    /// Explore the branches of the search on several threads, down to this depth
+    #[arg(long, value_name = "DEPTH", default_value_t = 0)]
+    parallel_depth: usize,
+
+    /// Give up each puzzle after solving this many lines
+    #[arg(long, value_name = "COUNT")]
+    max_line_solves: Option<u128>,
+
+    /// Give up each puzzle after this many backtracks
+    #[arg(long, value_name = "COUNT")]
+    max_backtracks: Option<u128>,
+
+    /// How to choose the pixel to branch on when line solving gets stuck
+    #[arg(long, value_enum, value_name = "HEURISTIC", default_value_t = Heuristic::Probability)]
+    branch: Heuristic,
+
+    /// The seed of `--branch random`
+    #[arg(long, value_name = "SEED", default_value_t = 0)]
+    seed: u64,
+
+    /// In which order to solve the lines waiting to be solved
+    #[arg(long, value_enum, value_name = "POLICY", default_value_t = QueuePolicy::Probability)]
+    line_priority: QueuePolicy,
+
+    /// Before each branch, try the colors of the pixels one by one to deduce more
+    #[arg(long)]
+    probe: bool,
+
+    /// Convert the puzzle into this format instead of solving it
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["format", "stats", "quiet", "report", "parallel"])]
+    convert: Option<ConvertFormat>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    /// An SVG picture of the grid with the clues, for a single puzzle
+    Svg,
+    /// The grid in the colors of the puzzle, for terminals with 24-bit colors
+    Ansi,
+    /// The grid as a JSON matrix of color indices
+    Grid,
+    /// The grid as the `goal` string of the `.non` format
+    Goal,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum StatsFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ConvertFormat {
+    Json,
+    Non,
+    G,
+    Nin,
+    Cwd,
+}
+
+impl From<ConvertFormat> for PuzzleFormat {
+    fn from(format: ConvertFormat) -> Self {
+        match format {
+            ConvertFormat::Json => PuzzleFormat::Json,
+            ConvertFormat::Non => PuzzleFormat::Non,
+            ConvertFormat::G => PuzzleFormat::Olsak,
+            ConvertFormat::Nin => PuzzleFormat::Nin,
+            ConvertFormat::Cwd => PuzzleFormat::Cwd,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Heuristic {
+    /// Where the row and the column disagree the most about a color
+    Probability,
+    /// The pixel with the fewest colors left, then the fewest arrangements of its lines
+    MostConstrained,
+    /// The color the most uncertain over the row and the column
+    MaxEntropy,
+    /// The first undetermined pixel in row-major order
+    First,
+    /// A pixel at random, given by `--seed`
+    Random,
+}
+
+impl Heuristic {
+    fn to_branch_heuristic(self, seed: u64) -> Arc<dyn BranchHeuristic> {
+        match self {
+            Heuristic::Probability => Arc::new(ProbabilityHeuristic),
+            Heuristic::MostConstrained => Arc::new(MostConstrainedHeuristic),
+            Heuristic::MaxEntropy => Arc::new(MaxEntropyHeuristic),
+            Heuristic::First => Arc::new(FirstUndeterminedHeuristic),
+            Heuristic::Random => Arc::new(RandomHeuristic::new(seed)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum QueuePolicy {
+    /// The lines whose arrangements were cut the most
+    Probability,
+    /// The lines in the order they were queued
+    Fifo,
+    /// The lines with the most changed pixels
+    ChangedCells,
+    /// The lines with the least free space left, counting their undetermined pixels
+    FreeSpace,
+}
+
+impl QueuePolicy {
+    fn to_line_priority(self) -> Arc<dyn LinePriority> {
+        match self {
+            QueuePolicy::Probability => Arc::new(ProbabilityPriority),
+            QueuePolicy::Fifo => Arc::new(FifoPriority),
+            QueuePolicy::ChangedCells => Arc::new(ChangedCellsPriority),
+            QueuePolicy::FreeSpace => Arc::new(FreeSpacePriority),
+        }
+    }
+}
+
+/// Exit codes. Among several inputs, the most severe outcome wins, which is not the highest code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Solved = 0,
+    PartiallySolved = 1,
+    Conflict = 2,
+    InputError = 3,
+    UsageError = 4,
+    GoalMismatch = 5,
+    Interrupted = 6,
+}
+
+impl Status {
+    /// Errors that kept a puzzle from being solved come first, then the puzzles solved
+    /// with an unexpected result, and the fully solved ones last.
+    fn severity(self) -> u8 {
+        match self {
+            Status::Solved => 0,
+            Status::PartiallySolved => 1,
+            Status::Conflict => 2,
+            Status::GoalMismatch => 3,
+            Status::Interrupted => 4,
+            Status::InputError => 5,
+            Status::UsageError => 6,
+        }
+    }
+
+    fn worst(self, other: Self) -> Self {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl From<SolveResult> for Status {
+    fn from(result: SolveResult) -> Self {
+        match result {
+            SolveResult::FullySolved => Status::Solved,
+            SolveResult::PartiallySolved => Status::PartiallySolved,
+            SolveResult::Conflict => Status::Conflict,
+            SolveResult::TimedOut | SolveResult::Cancelled | SolveResult::BudgetExhausted => {
+                Status::Interrupted
+            }
+        }
+    }
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    input: &'a Path,
+    #[serde(flatten)]
+    output: &'a SolveOutput,
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(error) if error.use_stderr() => {
+            let _ = error.print();
+            return Status::UsageError.into();
+        }
+        Err(error) => error.exit(),
+    };
+
+    match run(&cli) {
+        Ok(status) => status.into(),
+        Err(error) => {
+            eprintln!("Error: {error:#}");
+            Status::InputError.into()
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<Status> {
+    let mut writer: Box<dyn Write> = match &cli.output {
+        Some(path) => Box::new(BufWriter::new(create_file(path)?)),
+        None => Box::new(stdout().lock()),
+    };
+
+    let mut status = Status::Solved;
+    let mut paths = Vec::new();
+    for input in &cli.inputs {
+        if input.is_dir() {
+            match puzzle_paths(input) {
+                Ok(dir_paths) => paths.extend(dir_paths),
+                Err(error) => {
+                    eprintln!("{}: {error:#}", input.display());
+                    status = status.worst(Status::InputError);
+                }
+            }
+        } else {
+            paths.push(input.clone());
+        }
+    }
+
+    if let Some(format) = cli.convert {
+        let [path] = paths.as_slice() else {
+            eprintln!("Error: --convert takes a single puzzle");
+            return Ok(Status::UsageError);
+        };
+        read_puzzle(path)?.write(&mut writer, format.into())?;
+        writer.flush()?;
+        return Ok(status);
+    }
+
+    if matches!(cli.format, Format::Svg) && paths.len() > 1 {
+        eprintln!("Error: --format svg takes a single puzzle");
+        return Ok(Status::UsageError);
+    }
+
+    let options = SolveOptions {
+        timeout: cli.timeout,
+        max_line_solves: cli.max_line_solves,
+        max_backtracks: cli.max_backtracks,
+        parallel_depth: cli.parallel_depth,
+        branch_heuristic: Some(cli.branch.to_branch_heuristic(cli.seed)),
+        line_priority: Some(cli.line_priority.to_line_priority()),
+        probing: cli.probe,
+        ..SolveOptions::default()
+    };
+    let entries = solve_batch(&paths, cli.parallel, &options, read_puzzle);
+    for entry in &entries {
+        match &entry.output {
+            Ok(output) => {
+                status = status.worst(output.result.into());
+                if entry.matches_goal == Some(false) {
+                    eprintln!(
+                        "{}: the solution does not match the goal",
+                        entry.path.display()
+                    );
+                    status = status.worst(Status::GoalMismatch);
+                }
+
+                if !cli.quiet {
+                    write_output(
+                        &mut writer,
+                        cli,
+                        &entry.path,
+                        entry.puzzle.as_ref(),
+                        output,
+                        paths.len() > 1,
+                    )?;
+                }
+                if let Some(stats_format) = cli.stats {
+                    print_stats(stats_format, &entry.path, &output.stats);
+                }
+            }
+            Err(error) => {
+                eprintln!("{}: {error:#}", entry.path.display());
+                status = status.worst(Status::InputError);
+            }
+        }
+    }
+    writer.flush()?;
+
+    if let Some(path) = &cli.report {
+        let mut report = BufWriter::new(create_file(path)?);
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            write_json_report(&entries, &mut report)?;
+        } else {
+            write_csv_report(&entries, &mut report)?;
+        }
+        report.flush()?;
+    }
+
+    Ok(status)
+}
+
+fn parse_seconds(seconds: &str) -> Result<Duration> {
+    Ok(Duration::try_from_secs_f64(seconds.parse()?)?)
+}
+
+fn create_file(path: &Path) -> Result<File> {
+    File::create(path).with_context(|| format!("Cannot create the path \"{}\"", path.display()))
+}
+
+fn read_puzzle(input: &Path) -> Result<Puzzle> {
+    if input == Path::new("-") {
+        let mut bytes = Vec::new();
+        stdin().read_to_end(&mut bytes)?;
+        Puzzle::from_bytes(&bytes, PuzzleFormat::sniff(&bytes))
+    } else {
+        Puzzle::from_path(input)
+    }
+}
+
+fn write_output(
+    writer: &mut impl Write,
+    cli: &Cli,
+    input: &Path,
+    puzzle: Option<&Puzzle>,
+    output: &SolveOutput,
+    show_header: bool,
+) -> Result<()> {
+    match cli.format {
+        Format::Text => {
+            if show_header {
+                writeln!(writer, "{}:", input.display())?;
+            }
+            if output.result != SolveResult::Conflict {
+                output.solution.write_blank_possibility(writer)?;
+            }
+            writeln!(writer, "result: {:?}", output.result)?;
+        }
+        Format::Json => {
+            serde_json::to_writer(&mut *writer, &JsonOutput { input, output })?;
+            writeln!(writer)?;
+        }
+        Format::Svg => {
+            if let Some(puzzle) = puzzle {
+                output.solution.write_svg(puzzle, writer)?;
+            }
+        }
+        Format::Ansi => {
+            if show_header {
+                writeln!(writer, "{}:", input.display())?;
+            }
+            let clues = if cli.clues { puzzle } else { None };
+            output.solution.write_ansi(clues, writer)?;
+            writeln!(writer, "result: {:?}", output.result)?;
+        }
+        Format::Grid => {
+            if show_header {
+                writeln!(writer, "{}:", input.display())?;
+            }
+            let undetermined = if cli.null_undetermined {
+                Undetermined::Null
+            } else {
+                Undetermined::Candidates
+            };
+            output.solution.write_grid_json(undetermined, writer)?;
+        }
+        Format::Goal => {
+            if show_header {
+                writeln!(writer, "{}:", input.display())?;
+            }
+            match output.solution.to_non_goal() {
+                Some(goal) => writeln!(writer, "{goal}")?,
+                None => eprintln!("{}: the grid has no goal string", input.display()),
+            }
+        }
+    }
 
     Ok(())
 }
+
+fn print_stats(format: StatsFormat, input: &Path, stats: &SolveStats) {
+    match format {
+        StatsFormat::Text => {
+            eprintln!("{}:", input.display());
+            eprintln!("line_solves: {}", stats.line_solves);
+            eprintln!("backtracks:  {}", stats.backtracks);
+            eprintln!("max_depth:   {}", stats.max_depth);
+            eprintln!("cpu-time: {} sec", stats.cpu_time.as_secs_f64());
+            eprintln!("    time: {} sec", stats.wall_time.as_secs_f64());
+        }
+        StatsFormat::Json => eprintln!("{}", stats.to_json()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_outrank_every_solve_result() {
+        let status = Status::InputError.worst(Status::Interrupted);
+        assert_eq!(status, Status::InputError);
+        assert_eq!(Status::Interrupted.worst(Status::InputError), status);
+        assert_eq!(status.worst(Status::UsageError), Status::UsageError);
+        assert_eq!(
+            Status::Conflict.worst(Status::GoalMismatch),
+            Status::GoalMismatch
+        );
+        assert_eq!(
+            Status::PartiallySolved.worst(Status::Solved),
+            Status::PartiallySolved
+        );
+    }
+}
@@ -1,32 +1,212 @@
+pub mod batch;
+pub mod check;
+pub mod format;
+pub mod render;
+pub mod solution;
 pub mod solve;
+pub mod validation;
 
 use anyhow::{Context, Result};
-use serde::Deserialize;
-use serde_tuple::Deserialize_tuple;
-use std::fs::File;
-use std::io::BufReader;
+use format::Format;
+use serde::{Deserialize, Serialize};
+use serde_tuple::{Deserialize_tuple, Serialize_tuple};
+use solution::{Cell, Solution};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+use validation::PuzzleError;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Puzzle {
     colors: Vec<String>,
     clues: (Vec<LineClue>, Vec<LineClue>),
+    #[serde(skip)]
+    goal: Option<Solution>,
 }
 
 impl Puzzle {
+    /// Creates a puzzle from its colors, of which the first is the background, and its clues.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the puzzle is invalid.
+    pub fn new(
+        colors: Vec<String>,
+        rows: Vec<Vec<Description>>,
+        columns: Vec<Vec<Description>>,
+    ) -> Result<Self, PuzzleError> {
+        let puzzle = Self {
+            colors,
+            clues: (rows, columns),
+            goal: None,
+        };
+        puzzle.validate()?;
+
+        Ok(puzzle)
+    }
+
+    /// Creates the puzzle whose solution is `grid`, a matrix of color indices,
+    /// with `grid` as its goal.
+    ///
+    /// Adjacent pixels of the same color in a line form one description of its clue.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows of `grid` do not all have the same length,
+    /// or if the puzzle is invalid, e.g. if `grid` uses an unknown color.
+    pub fn from_grid(colors: Vec<String>, grid: &[Vec<usize>]) -> Result<Self, PuzzleError> {
+        let width = grid.first().map_or(0, Vec::len);
+        if let Some((row_index, row)) = grid.iter().enumerate().find(|(_, row)| row.len() != width)
+        {
+            return Err(PuzzleError::RaggedGrid {
+                row_index,
+                length: row.len(),
+                width,
+            });
+        }
+        let rows = grid
+            .iter()
+            .map(|row| calc_line_clue(row.iter().copied()))
+            .collect();
+        let columns = (0..width)
+            .map(|column_index| calc_line_clue(grid.iter().map(|row| row[column_index])))
+            .collect();
+
+        let cells = grid
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&color_index| Cell::Color(color_index))
+                    .collect()
+            })
+            .collect();
+        let goal = Solution::new(colors.clone(), cells);
+
+        let mut puzzle = Self::new(colors, rows, columns)?;
+        puzzle.goal = Some(goal);
+
+        Ok(puzzle)
+    }
+
+    /// Reads `puzzles/{puzzle_name}.json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or is not a valid puzzle.
     pub fn from_json(puzzle_name: &str) -> Result<Self> {
-        let path = &format!("puzzles/{}.json", puzzle_name);
-        let file = File::open(path).with_context(|| format!("Cannot open the path \"{}\"", path))?;
+        Self::from_path(format!("puzzles/{puzzle_name}.json"))
+    }
 
-        let reader = BufReader::new(file);
+    /// Reads a puzzle from a file, in the format given by its extension
+    /// or else guessed from its content.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid puzzle.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)
+            .with_context(|| format!("Cannot open the path \"{}\"", path.display()))?;
+        let format = Format::from_extension(path).unwrap_or_else(|| Format::sniff(&bytes));
 
-        serde_json::from_reader(reader).context("The JSON file has an unexpected structure")
+        Self::from_bytes(&bytes, format)
+    }
+
+    /// Reads a puzzle in the JSON format from a reader.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or the input is not a valid puzzle.
+    pub fn from_reader(reader: impl Read) -> Result<Self> {
+        let puzzle: Self = serde_json::from_reader(reader)
+            .context("The JSON input has an unexpected structure")?;
+        puzzle.validate()?;
+
+        Ok(puzzle)
+    }
+
+    /// The solution given by the puzzle file, if any, against which the solver output can be checked.
+    #[must_use]
+    pub fn get_goal(&self) -> Option<&Solution> {
+        self.goal.as_ref()
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let puzzle: Self =
+            serde_json::from_str(s).context("The JSON input has an unexpected structure")?;
+        puzzle.validate()?;
+
+        Ok(puzzle)
+    }
+}
+
+/// A row or a column of a puzzle, identified by its 0-based index.
+///
+/// It is displayed with a 1-based number, as puzzles are usually numbered, e.g. `row 1`.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum LineId {
+    Row(usize),
+    Column(usize),
+}
+
+impl fmt::Display for LineId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineId::Row(index) => write!(f, "row {}", index + 1),
+            LineId::Column(index) => write!(f, "column {}", index + 1),
+        }
     }
 }
 
 type LineClue = Vec<Description>;
 
-#[derive(Debug, Deserialize_tuple)]
-struct Description {
+fn calc_line_clue(line: impl Iterator<Item = usize>) -> LineClue {
+    let mut line_clue: LineClue = Vec::new();
+    let mut previous_color_index = 0;
+    for color_index in line {
+        if color_index != 0 {
+            match line_clue.last_mut() {
+                Some(description) if color_index == previous_color_index => {
+                    description.number += 1;
+                }
+                _ => line_clue.push(Description::new(color_index, 1)),
+            }
+        }
+        previous_color_index = color_index;
+    }
+
+    line_clue
+}
+
+/// A run of `number` pixels of the color `color_index` in a clue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize_tuple, Deserialize_tuple)]
+pub struct Description {
     color_index: usize,
     number: usize,
 }
+
+impl Description {
+    #[must_use]
+    pub fn new(color_index: usize, number: usize) -> Self {
+        Self {
+            color_index,
+            number,
+        }
+    }
+
+    #[must_use]
+    pub fn get_color_index(&self) -> usize {
+        self.color_index
+    }
+
+    #[must_use]
+    pub fn get_number(&self) -> usize {
+        self.number
+    }
+}
@@ -0,0 +1,185 @@
+use super::{
+    format::Format,
+    solve::{solve_with, SolveOptions, SolveOutput, SolveResult, SolveStats},
+    Puzzle,
+};
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The outcome of one puzzle of a batch.
+#[derive(Debug)]
+pub struct BatchEntry {
+    pub path: PathBuf,
+    /// The puzzle, if it could be loaded.
+    pub puzzle: Option<Puzzle>,
+    /// The error is the one of loading the puzzle.
+    pub output: Result<SolveOutput>,
+    /// Whether the puzzle was fully solved into its goal, if it has one and the solve finished.
+    pub matches_goal: Option<bool>,
+}
+
+/// Lists the puzzle files of a directory, sorted by name.
+///
+/// Text art and images are left out, since a directory of puzzles may also hold a `README.txt`
+/// or screenshots. They are read only when given explicitly.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn puzzle_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("Cannot read the directory \"{}\"", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && Format::from_extension(&path).is_some_and(Format::has_clues) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Loads each puzzle with `load` and solves it with `options`, keeping the order of `paths`.
+/// The timeout of `options` applies to each puzzle separately.
+///
+/// With `parallel`, the puzzles are spread over the CPU cores. The CPU times of the statistics
+/// are those of the whole process, so they then include the puzzles solved at the same time.
+pub fn solve_batch<F>(
+    paths: &[PathBuf],
+    parallel: bool,
+    options: &SolveOptions,
+    load: F,
+) -> Vec<BatchEntry>
+where
+    F: Fn(&Path) -> Result<Puzzle> + Sync,
+{
+    let solve_path = |path: &PathBuf| match load(path) {
+        Ok(puzzle) => {
+            let output = solve_with(&puzzle, options);
+            let matches_goal = puzzle
+                .get_goal()
+                .filter(|_| !output.result.is_interrupted())
+                .map(|goal| output.result == SolveResult::FullySolved && output.solution == *goal);
+            BatchEntry {
+                path: path.clone(),
+                puzzle: Some(puzzle),
+                output: Ok(output),
+                matches_goal,
+            }
+        }
+        Err(error) => BatchEntry {
+            path: path.clone(),
+            puzzle: None,
+            output: Err(error),
+            matches_goal: None,
+        },
+    };
+
+    if parallel {
+        paths.par_iter().map(solve_path).collect()
+    } else {
+        paths.iter().map(solve_path).collect()
+    }
+}
+
+#[derive(Serialize)]
+struct ReportRow<'a> {
+    path: &'a Path,
+    result: Option<SolveResult>,
+    matches_goal: Option<bool>,
+    #[serde(flatten)]
+    stats: Option<&'a SolveStats>,
+    error: Option<String>,
+}
+
+impl<'a> From<&'a BatchEntry> for ReportRow<'a> {
+    fn from(entry: &'a BatchEntry) -> Self {
+        match &entry.output {
+            Ok(output) => Self {
+                path: &entry.path,
+                result: Some(output.result),
+                matches_goal: entry.matches_goal,
+                stats: Some(&output.stats),
+                error: None,
+            },
+            Err(error) => Self {
+                path: &entry.path,
+                result: None,
+                matches_goal: None,
+                stats: None,
+                error: Some(format!("{error:#}")),
+            },
+        }
+    }
+}
+
+/// Writes one JSON object per puzzle, with the result and the statistics, as a JSON array.
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_json_report(entries: &[BatchEntry], writer: &mut impl Write) -> Result<()> {
+    let rows: Vec<ReportRow> = entries.iter().map(ReportRow::from).collect();
+    serde_json::to_writer_pretty(&mut *writer, &rows)?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+/// Writes one CSV line per puzzle, with the result and the statistics, after a header line.
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_csv_report(entries: &[BatchEntry], writer: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        writer,
+        "path,result,matches_goal,line_solves,backtracks,max_depth,probability_recomputations,wall_time,cpu_time,error"
+    )?;
+
+    for row in entries.iter().map(ReportRow::from) {
+        write!(writer, "{}", escape_csv(&row.path.display().to_string()))?;
+        match row.result {
+            Some(result) => write!(writer, ",{result:?}")?,
+            None => write!(writer, ",")?,
+        }
+        match row.matches_goal {
+            Some(matches_goal) => write!(writer, ",{matches_goal}")?,
+            None => write!(writer, ",")?,
+        }
+        match row.stats {
+            Some(stats) => write!(
+                writer,
+                ",{},{},{},{},{},{}",
+                stats.line_solves,
+                stats.backtracks,
+                stats.max_depth,
+                stats.probability_recomputations,
+                stats.wall_time.as_secs_f64(),
+                stats.cpu_time.as_secs_f64()
+            )?,
+            None => write!(writer, ",,,,,,")?,
+        }
+        writeln!(
+            writer,
+            ",{}",
+            escape_csv(row.error.as_deref().unwrap_or_default())
+        )?;
+    }
+
+    Ok(())
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,437 @@
+use super::{
+    calc_line_clue,
+    solution::{Cell, Solution},
+    LineClue, LineId, Puzzle,
+};
+use std::fmt;
+
+/// A way in which a line of a grid does not satisfy its clue.
+///
+/// Pixel and description indices are 0-based, but displayed 1-based like `LineId`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineViolation {
+    /// The line does not have as many pixels as in the puzzle.
+    WrongLength {
+        line_id: LineId,
+        expected: usize,
+        actual: usize,
+    },
+    /// A pixel has several candidate colors.
+    Undetermined { line_id: LineId, pixel_index: usize },
+    /// The line does not have as many runs as descriptions.
+    RunCount {
+        line_id: LineId,
+        expected: usize,
+        actual: usize,
+    },
+    /// A run is longer or shorter than its description.
+    RunLength {
+        line_id: LineId,
+        description_index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// A run does not have the color of its description.
+    RunColor {
+        line_id: LineId,
+        description_index: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// The runs of a description and of the next one, of the same color, touch each other.
+    MissingSeparator {
+        line_id: LineId,
+        description_index: usize,
+    },
+}
+
+impl LineViolation {
+    #[must_use]
+    pub fn get_line_id(&self) -> LineId {
+        match self {
+            LineViolation::WrongLength { line_id, .. }
+            | LineViolation::Undetermined { line_id, .. }
+            | LineViolation::RunCount { line_id, .. }
+            | LineViolation::RunLength { line_id, .. }
+            | LineViolation::RunColor { line_id, .. }
+            | LineViolation::MissingSeparator { line_id, .. } => *line_id,
+        }
+    }
+}
+
+impl fmt::Display for LineViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineViolation::WrongLength {
+                line_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "There are {actual} pixels in {line_id} instead of {expected}"
+            ),
+            LineViolation::Undetermined {
+                line_id,
+                pixel_index,
+            } => write!(
+                f,
+                "Pixel {} of {line_id} has no single color",
+                pixel_index + 1
+            ),
+            LineViolation::RunCount {
+                line_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "There are {actual} runs in {line_id} instead of {expected}"
+            ),
+            LineViolation::RunLength {
+                line_id,
+                description_index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Run {} of {line_id} has {actual} pixels instead of {expected}",
+                description_index + 1
+            ),
+            LineViolation::RunColor {
+                line_id,
+                description_index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Run {} of {line_id} has the color {actual} instead of {expected}",
+                description_index + 1
+            ),
+            LineViolation::MissingSeparator {
+                line_id,
+                description_index,
+            } => write!(
+                f,
+                "Runs {} and {} of {line_id} have the same color but no blank between them",
+                description_index + 1,
+                description_index + 2
+            ),
+        }
+    }
+}
+
+impl Puzzle {
+    /// Checks each row and then each column of `solution` against its clue,
+    /// returning every violation found, or none if `solution` solves the puzzle.
+    #[must_use]
+    pub fn check(&self, solution: &Solution) -> Vec<LineViolation> {
+        let (row_clues, column_clues) = &self.clues;
+        let rows = solution.get_rows();
+
+        let mut violations = Vec::new();
+        for (row_index, line_clue) in row_clues.iter().enumerate() {
+            let line: Vec<&Cell> = rows.get(row_index).into_iter().flatten().collect();
+            violations.extend(check_line(
+                LineId::Row(row_index),
+                line_clue,
+                &line,
+                column_clues.len(),
+            ));
+        }
+        for (column_index, line_clue) in column_clues.iter().enumerate() {
+            let line: Vec<&Cell> = rows
+                .iter()
+                .filter_map(|row| row.get(column_index))
+                .collect();
+            violations.extend(check_line(
+                LineId::Column(column_index),
+                line_clue,
+                &line,
+                row_clues.len(),
+            ));
+        }
+
+        violations
+    }
+}
+
+fn check_line(
+    line_id: LineId,
+    line_clue: &LineClue,
+    line: &[&Cell],
+    length: usize,
+) -> Vec<LineViolation> {
+    if line.len() != length {
+        return vec![LineViolation::WrongLength {
+            line_id,
+            expected: length,
+            actual: line.len(),
+        }];
+    }
+
+    let undetermined: Vec<LineViolation> = line
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| !cell.is_determined())
+        .map(|(pixel_index, _)| LineViolation::Undetermined {
+            line_id,
+            pixel_index,
+        })
+        .collect();
+    if !undetermined.is_empty() {
+        return undetermined;
+    }
+
+    let runs = calc_line_clue(line.iter().filter_map(|cell| cell.get_color_index()));
+    if runs.len() == line_clue.len() {
+        return line_clue
+            .iter()
+            .zip(&runs)
+            .enumerate()
+            .filter_map(|(description_index, (expected, actual))| {
+                if expected.color_index != actual.color_index {
+                    Some(LineViolation::RunColor {
+                        line_id,
+                        description_index,
+                        expected: expected.color_index,
+                        actual: actual.color_index,
+                    })
+                } else if expected.number != actual.number {
+                    Some(LineViolation::RunLength {
+                        line_id,
+                        description_index,
+                        expected: expected.number,
+                        actual: actual.number,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+    }
+
+    match find_missing_separators(line_clue, &runs) {
+        Some(description_indices) => description_indices
+            .into_iter()
+            .map(|description_index| LineViolation::MissingSeparator {
+                line_id,
+                description_index,
+            })
+            .collect(),
+        None => vec![LineViolation::RunCount {
+            line_id,
+            expected: line_clue.len(),
+            actual: runs.len(),
+        }],
+    }
+}
+
+/// Explains the runs as the descriptions with some neighbors of the same color joined,
+/// returning the index of the first description of each joined pair.
+fn find_missing_separators(line_clue: &LineClue, runs: &LineClue) -> Option<Vec<usize>> {
+    let mut description_indices = Vec::new();
+    let mut description_index = 0;
+    for run in runs {
+        let description = line_clue.get(description_index)?;
+        if description.color_index != run.color_index {
+            return None;
+        }
+
+        let mut total = description.number;
+        while total < run.number {
+            let next = line_clue
+                .get(description_index + 1)
+                .filter(|next| next.color_index == run.color_index)?;
+            description_indices.push(description_index);
+            description_index += 1;
+            total += next.number;
+        }
+        if total != run.number {
+            return None;
+        }
+        description_index += 1;
+    }
+
+    (description_index == line_clue.len()).then_some(description_indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Description;
+
+    const BLACK: usize = 1;
+    const RED: usize = 2;
+    const ROW: LineId = LineId::Row(0);
+
+    /// Checks a row of `cells`, where `None` is a pixel that is white or black.
+    fn check(clue: &[(usize, usize)], cells: &[Option<usize>]) -> Vec<LineViolation> {
+        let line_clue: LineClue = clue
+            .iter()
+            .map(|&(color_index, number)| Description::new(color_index, number))
+            .collect();
+        let cells: Vec<Cell> = cells
+            .iter()
+            .map(|cell| match cell {
+                Some(color_index) => Cell::Color(*color_index),
+                None => Cell::Candidates(vec![0, BLACK]),
+            })
+            .collect();
+        let line: Vec<&Cell> = cells.iter().collect();
+
+        check_line(ROW, &line_clue, &line, line.len())
+    }
+
+    #[test]
+    fn solved_line_has_no_violation() {
+        let violations = check(
+            &[(BLACK, 2), (RED, 1)],
+            &[Some(0), Some(1), Some(1), Some(2)],
+        );
+        assert!(violations.is_empty(), "{violations:?}");
+    }
+
+    #[test]
+    fn line_of_another_length_is_reported() {
+        let cells = [Cell::Color(0), Cell::Color(BLACK)];
+        let line: Vec<&Cell> = cells.iter().collect();
+        let violations = check_line(ROW, &vec![Description::new(BLACK, 1)], &line, 3);
+        assert_eq!(
+            violations,
+            [LineViolation::WrongLength {
+                line_id: ROW,
+                expected: 3,
+                actual: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn every_undetermined_pixel_is_reported() {
+        let violations = check(&[(BLACK, 1)], &[None, Some(0), None]);
+        assert_eq!(
+            violations,
+            [
+                LineViolation::Undetermined {
+                    line_id: ROW,
+                    pixel_index: 0
+                },
+                LineViolation::Undetermined {
+                    line_id: ROW,
+                    pixel_index: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_run_is_a_run_count() {
+        let violations = check(&[(BLACK, 1), (BLACK, 1)], &[Some(1), Some(0), Some(0)]);
+        assert_eq!(
+            violations,
+            [LineViolation::RunCount {
+                line_id: ROW,
+                expected: 2,
+                actual: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn run_of_another_length_is_reported() {
+        let violations = check(
+            &[(BLACK, 1), (RED, 2)],
+            &[Some(1), Some(1), Some(2), Some(2)],
+        );
+        assert_eq!(
+            violations,
+            [LineViolation::RunLength {
+                line_id: ROW,
+                description_index: 0,
+                expected: 1,
+                actual: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn run_of_another_color_is_reported() {
+        let violations = check(&[(BLACK, 1), (RED, 1)], &[Some(2), Some(0), Some(1)]);
+        assert_eq!(
+            violations,
+            [
+                LineViolation::RunColor {
+                    line_id: ROW,
+                    description_index: 0,
+                    expected: BLACK,
+                    actual: RED
+                },
+                LineViolation::RunColor {
+                    line_id: ROW,
+                    description_index: 1,
+                    expected: RED,
+                    actual: BLACK
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_of_the_same_color_without_blank_are_reported() {
+        let violations = check(&[(BLACK, 1), (BLACK, 2)], &[Some(1), Some(1), Some(1)]);
+        assert_eq!(
+            violations,
+            [LineViolation::MissingSeparator {
+                line_id: ROW,
+                description_index: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn run_joining_three_descriptions_misses_two_separators() {
+        let violations = check(
+            &[(RED, 1), (BLACK, 1), (BLACK, 2), (BLACK, 1)],
+            &[Some(2), Some(1), Some(1), Some(1), Some(1)],
+        );
+        assert_eq!(
+            violations,
+            [
+                LineViolation::MissingSeparator {
+                    line_id: ROW,
+                    description_index: 1
+                },
+                LineViolation::MissingSeparator {
+                    line_id: ROW,
+                    description_index: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn run_joining_descriptions_of_different_colors_is_a_run_count() {
+        // The black run cannot stand for the black and the red descriptions together.
+        let violations = check(&[(BLACK, 2), (RED, 1)], &[Some(1), Some(1), Some(1)]);
+        assert_eq!(
+            violations,
+            [LineViolation::RunCount {
+                line_id: ROW,
+                expected: 2,
+                actual: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn joined_runs_of_the_wrong_total_are_a_run_count() {
+        let violations = check(&[(BLACK, 1), (BLACK, 1)], &[Some(1), Some(1), Some(1)]);
+        assert_eq!(
+            violations,
+            [LineViolation::RunCount {
+                line_id: ROW,
+                expected: 2,
+                actual: 1
+            }]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+pub mod cwd;
+pub mod image;
+pub mod nin;
+pub mod non;
+pub mod olsak;
+pub mod text_art;
+pub mod webpbn;
+
+use super::{Description, LineClue, Puzzle};
+use anyhow::{bail, ensure, Context, Result};
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::Path;
+
+/// The formats puzzles can be read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The JSON layout of this project.
+    Json,
+    /// The XML format of webpbn.com.
+    Webpbn,
+    /// Steve Simpson's `.non` format.
+    Non,
+    /// Mirek Olšák's `.g` format.
+    Olsak,
+    /// The `.nin` format: the width and the height, then one line of numbers per clue.
+    Nin,
+    /// The CWD format: the height, the width, then one line of numbers per clue.
+    Cwd,
+    /// A picture drawn with characters, optionally preceded by a palette.
+    TextArt,
+    /// A PNG or Netpbm picture.
+    Image,
+}
+
+impl Format {
+    /// Guesses the format from the extension of `path`.
+    #[must_use]
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension().and_then(OsStr::to_str)? {
+            "json" => Some(Self::Json),
+            "xml" => Some(Self::Webpbn),
+            "non" => Some(Self::Non),
+            "g" => Some(Self::Olsak),
+            "nin" => Some(Self::Nin),
+            "cwd" => Some(Self::Cwd),
+            "txt" => Some(Self::TextArt),
+            "png" | "pbm" | "pgm" | "ppm" | "pnm" => Some(Self::Image),
+            _ => None,
+        }
+    }
+
+    /// Whether the format writes a puzzle by its clues, rather than a picture to make one of.
+    /// Only these are picked up from directories, where pictures may be anything.
+    #[must_use]
+    pub fn has_clues(self) -> bool {
+        !matches!(self, Self::TextArt | Self::Image)
+    }
+
+    /// Guesses the format from the magic number of an image,
+    /// or else from the first line of the text which is not blank, defaulting to JSON.
+    #[must_use]
+    pub fn sniff(bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"\x89PNG")
+            || (bytes.len() >= 3
+                && bytes[0] == b'P'
+                && (b'1'..=b'6').contains(&bytes[1])
+                && bytes[2].is_ascii_whitespace())
+        {
+            return Self::Image;
+        }
+
+        let text = String::from_utf8_lossy(bytes);
+        let Some(line) = text.lines().map(str::trim).find(|line| !line.is_empty()) else {
+            return Self::Json;
+        };
+        let numbers = line.split_whitespace().map(str::parse::<usize>);
+        let mut chars = line.chars();
+        let is_palette_line =
+            chars.next().is_some() && chars.next().is_some_and(char::is_whitespace);
+
+        if line.starts_with(['{', '[']) {
+            Self::Json
+        } else if line.starts_with('<') {
+            Self::Webpbn
+        } else if line.starts_with(':') || line == "#d" {
+            Self::Olsak
+        } else if numbers.clone().all(|number| number.is_ok()) {
+            match numbers.count() {
+                1 => Self::Cwd,
+                2 => Self::Nin,
+                _ => Self::Json,
+            }
+        } else if is_palette_line || line.chars().all(|c| c == '.' || c == '#') {
+            Self::TextArt
+        } else if line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            Self::Non
+        } else {
+            Self::Json
+        }
+    }
+}
+
+impl Puzzle {
+    /// Reads a puzzle written in `format`, which is text unless it is an image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bytes` is not a valid puzzle in this format.
+    pub fn from_bytes(bytes: &[u8], format: Format) -> Result<Self> {
+        if format == Format::Image {
+            Self::from_image(bytes)
+        } else {
+            let text = std::str::from_utf8(bytes).context("The input is not UTF-8 text")?;
+            Self::from_text(text, format)
+        }
+    }
+
+    /// Reads a puzzle written in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `text` is not a valid puzzle in this format.
+    pub fn from_text(text: &str, format: Format) -> Result<Self> {
+        match format {
+            Format::Json => text.parse(),
+            Format::Webpbn => Self::from_webpbn(text),
+            Format::Non => Self::from_non(text),
+            Format::Olsak => Self::from_olsak(text),
+            Format::Nin => Self::from_nin(text),
+            Format::Cwd => Self::from_cwd(text),
+            Format::TextArt => Self::from_text_art(text),
+            Format::Image => Self::from_image(text.as_bytes()),
+        }
+    }
+
+    /// Writes the puzzle in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the puzzle cannot be written in this format, such as a colored puzzle
+    /// in a black and white format, or if writing fails.
+    pub fn write(&self, writer: &mut impl Write, format: Format) -> Result<()> {
+        match format {
+            Format::Json => {
+                serde_json::to_writer(&mut *writer, self)?;
+                writeln!(writer)?;
+                Ok(())
+            }
+            Format::Webpbn => bail!("Writing the webpbn format is not supported"),
+            Format::Non => self.write_non(writer),
+            Format::Olsak => self.write_olsak(writer),
+            Format::Nin => self.write_nin(writer),
+            Format::Cwd => self.write_cwd(writer),
+            Format::TextArt | Format::Image => {
+                bail!("Writing a puzzle as a picture is not supported")
+            }
+        }
+    }
+
+    fn black_and_white(rows: Vec<LineClue>, columns: Vec<LineClue>) -> Self {
+        Self {
+            colors: vec!["white".to_string(), "black".to_string()],
+            clues: (rows, columns),
+            goal: None,
+        }
+    }
+
+    fn ensure_black_and_white(&self, format_name: &str) -> Result<()> {
+        ensure!(
+            self.colors.len() == 2,
+            "The {format_name} format only allows black and white puzzles"
+        );
+
+        Ok(())
+    }
+}
+
+/// Parses the numbers of a black clue, separated by commas or spaces. `0` is an empty clue.
+fn parse_black_clue(line: &str) -> Result<LineClue> {
+    if line.is_empty() || line == "0" {
+        return Ok(Vec::new());
+    }
+
+    line.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|number| !number.is_empty())
+        .map(|number| {
+            Ok(Description {
+                color_index: 1,
+                number: number
+                    .parse()
+                    .with_context(|| format!("\"{line}\" is not a list of numbers"))?,
+            })
+        })
+        .collect()
+}
+
+/// Reads `count` black clues from `lines`, skipping the blank lines.
+fn read_black_clues<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    count: usize,
+) -> Result<Vec<LineClue>> {
+    let mut lines = lines.filter(|line| !line.is_empty());
+    (0..count)
+        .map(|index| {
+            let line = lines
+                .next()
+                .with_context(|| format!("Only {index} of the {count} clues are given"))?;
+            parse_black_clue(line)
+        })
+        .collect()
+}
+
+/// Writes the numbers of a black clue separated by `separator`, or `0` if the clue is empty.
+fn write_black_clue(writer: &mut impl Write, line_clue: &LineClue, separator: &str) -> Result<()> {
+    if line_clue.is_empty() {
+        writeln!(writer, "0")?;
+    } else {
+        let numbers: Vec<String> = line_clue
+            .iter()
+            .map(|description| description.number.to_string())
+            .collect();
+        writeln!(writer, "{}", numbers.join(separator))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use super::{read_black_clues, write_black_clue, Puzzle};
+use anyhow::{ensure, Context, Result};
+use std::io::Write;
+
+impl Puzzle {
+    /// Reads a black and white puzzle in the CWD format: a line with the height, a line with the width,
+    /// then the row clues and the column clues, one line of numbers each.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if some clues are missing, malformed or in excess, or if the puzzle is invalid.
+    pub fn from_cwd(text: &str) -> Result<Self> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let height = parse_size(lines.next(), "height")?;
+        let width = parse_size(lines.next(), "width")?;
+
+        let rows = read_black_clues(&mut lines, height).context("Invalid rows")?;
+        let columns = read_black_clues(&mut lines, width).context("Invalid columns")?;
+        ensure!(
+            lines.next().is_none(),
+            "The input has more clues than its {height} rows and {width} columns"
+        );
+
+        let puzzle = Self::black_and_white(rows, columns);
+        puzzle.validate()?;
+
+        Ok(puzzle)
+    }
+
+    /// Writes the puzzle in the CWD format, with a blank line before the row and the column clues.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the puzzle is not black and white or if writing fails.
+    pub fn write_cwd(&self, writer: &mut impl Write) -> Result<()> {
+        self.ensure_black_and_white("CWD")?;
+
+        writeln!(writer, "{}", self.clues.0.len())?;
+        writeln!(writer, "{}", self.clues.1.len())?;
+        for line_clues in [&self.clues.0, &self.clues.1] {
+            writeln!(writer)?;
+            for line_clue in line_clues {
+                write_black_clue(writer, line_clue, " ")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_size(line: Option<&str>, name: &str) -> Result<usize> {
+    let line = line.with_context(|| format!("The input has no {name}"))?;
+    line.parse()
+        .with_context(|| format!("The {name} \"{line}\" is not a number"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "2\n3\n\n1 1\n2\n\n1\n1\n2\n";
+
+    #[test]
+    fn round_trip_keeps_clues() {
+        let puzzle = Puzzle::from_cwd(FIXTURE).unwrap();
+        let mut written = Vec::new();
+        puzzle.write_cwd(&mut written).unwrap();
+        let reparsed = Puzzle::from_cwd(std::str::from_utf8(&written).unwrap()).unwrap();
+
+        assert_eq!(reparsed.clues, puzzle.clues);
+    }
+
+    #[test]
+    fn header_is_the_height_then_the_width() {
+        let puzzle = Puzzle::from_cwd(FIXTURE).unwrap();
+        assert_eq!(puzzle.clues.0.len(), 2);
+        assert_eq!(puzzle.clues.1.len(), 3);
+
+        let error = Puzzle::from_cwd("2\nthree\n\n1 1\n2\n\n1\n1\n2\n").unwrap_err();
+        assert_eq!(error.to_string(), "The width \"three\" is not a number");
+
+        let error = Puzzle::from_cwd("2\n").unwrap_err();
+        assert_eq!(error.to_string(), "The input has no width");
+    }
+
+    #[test]
+    fn dimensions_must_match_the_clues() {
+        let error = Puzzle::from_cwd("2\n2\n\n1 1\n2\n\n1\n1\n2\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The input has more clues than its 2 rows and 2 columns"
+        );
+
+        // With the dimensions swapped, the rows take the first column clue.
+        let error = Puzzle::from_cwd("3\n2\n\n1 1\n2\n\n1\n1\n2\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The descriptions of row 1 need 3 cells, but the line has only 2"
+        );
+
+        let error = Puzzle::from_cwd("2\n4\n\n1 1\n2\n\n1\n1\n2\n").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Invalid columns: Only 3 of the 4 clues are given"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+use super::Puzzle;
+use anyhow::{bail, ensure, Context, Result};
+use fxhash::FxHashMap;
+use png::{ColorType, Decoder, Transformations};
+
+type Rgb = [u8; 3];
+
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Puzzle {
+    /// Reads a picture in the PNG or Netpbm (PBM, PGM, PPM) format and makes a puzzle of it.
+    ///
+    /// Every distinct pixel color becomes a color of the puzzle, written as `#rrggbb`.
+    /// The background is white if the picture has some, and otherwise its most frequent color.
+    /// Transparent pixels are read as white.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the picture is malformed or makes an invalid puzzle,
+    /// e.g. one with too many colors.
+    pub fn from_image(bytes: &[u8]) -> Result<Self> {
+        let image = if bytes.starts_with(PNG_SIGNATURE) {
+            decode_png(bytes)?
+        } else if bytes.starts_with(b"P") {
+            decode_netpbm(bytes)?
+        } else {
+            bail!("The input is neither a PNG nor a Netpbm image");
+        };
+
+        // The colors in the order they first appear, with their number of pixels.
+        let mut palette: Vec<(Rgb, usize)> = Vec::new();
+        let mut palette_indices: FxHashMap<Rgb, usize> = FxHashMap::default();
+        for &pixel in &image.pixels {
+            if let Some(&index) = palette_indices.get(&pixel) {
+                palette[index].1 += 1;
+            } else {
+                ensure!(
+                    palette.len() < MAX_COLOR_NUM,
+                    "The image has more than {MAX_COLOR_NUM} colors"
+                );
+                palette_indices.insert(pixel, palette.len());
+                palette.push((pixel, 1));
+            }
+        }
+        let background_index = palette
+            .iter()
+            .position(|(rgb, _)| *rgb == WHITE)
+            .or_else(|| {
+                (0..palette.len()).max_by_key(|&index| (palette[index].1, usize::MAX - index))
+            });
+        if let Some(background_index) = background_index {
+            let background = palette.remove(background_index);
+            palette.insert(0, background);
+        }
+        for (index, (rgb, _)) in palette.iter().enumerate() {
+            palette_indices.insert(*rgb, index);
+        }
+
+        let grid: Vec<Vec<usize>> = image
+            .pixels
+            .chunks(image.width.max(1))
+            .take(image.height)
+            .map(|row| row.iter().map(|pixel| palette_indices[pixel]).collect())
+            .collect();
+        let colors = palette
+            .iter()
+            .map(|([red, green, blue], _)| format!("#{red:02x}{green:02x}{blue:02x}"))
+            .collect();
+
+        Ok(Self::from_grid(colors, &grid)?)
+    }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const WHITE: Rgb = [255, 255, 255];
+/// Far more pixels than any puzzle has, to refuse huge images before allocating them.
+const MAX_PIXEL_COUNT: usize = 1 << 20;
+/// The most colors a puzzle can have, as checked by `Puzzle::validate`.
+const MAX_COLOR_NUM: usize = usize::BITS as usize - 1;
+
+/// The number of pixels of an image whose size is read from untrusted input.
+fn calc_pixel_count(width: usize, height: usize) -> Result<usize> {
+    width
+        .checked_mul(height)
+        .filter(|&pixel_count| pixel_count <= MAX_PIXEL_COUNT)
+        .with_context(|| format!("The image of {width}x{height} pixels is too large"))
+}
+
+fn decode_png(bytes: &[u8]) -> Result<Image> {
+    let mut decoder = Decoder::new(bytes);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().context("The PNG image is malformed")?;
+    calc_pixel_count(reader.info().width as usize, reader.info().height as usize)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .context("The PNG image is malformed")?;
+
+    let samples = &buffer[..info.buffer_size()];
+    let pixels = match info.color_type {
+        ColorType::Grayscale => samples.iter().map(|&gray| [gray; 3]).collect(),
+        ColorType::GrayscaleAlpha => samples
+            .chunks_exact(2)
+            .map(|sample| {
+                if sample[1] == 0 {
+                    WHITE
+                } else {
+                    [sample[0]; 3]
+                }
+            })
+            .collect(),
+        ColorType::Rgb => samples
+            .chunks_exact(3)
+            .map(|sample| [sample[0], sample[1], sample[2]])
+            .collect(),
+        ColorType::Rgba => samples
+            .chunks_exact(4)
+            .map(|sample| {
+                if sample[3] == 0 {
+                    WHITE
+                } else {
+                    [sample[0], sample[1], sample[2]]
+                }
+            })
+            .collect(),
+        ColorType::Indexed => bail!("The PNG image has an unexpected color type"),
+    };
+
+    Ok(Image {
+        width: info.width as usize,
+        height: info.height as usize,
+        pixels,
+    })
+}
+
+/// Reads the plain (`P1` to `P3`) and raw (`P4` to `P6`) Netpbm images.
+fn decode_netpbm(bytes: &[u8]) -> Result<Image> {
+    let mut header = NetpbmHeader { bytes, position: 0 };
+    let magic = header
+        .next_token()
+        .context("The Netpbm image has no header")?;
+    // The bitmaps have no channel but one bit per pixel.
+    let channels = match magic {
+        b"P1" | b"P4" => 0,
+        b"P2" | b"P5" => 1,
+        b"P3" | b"P6" => 3,
+        _ => bail!("The input is not a Netpbm image"),
+    };
+    let plain = matches!(magic, b"P1" | b"P2" | b"P3");
+
+    let width = header.next_number()?;
+    let height = header.next_number()?;
+    let max_value = if channels == 0 {
+        1
+    } else {
+        header.next_number()?
+    };
+    ensure!(
+        (1..=u16::MAX.into()).contains(&max_value),
+        "The Netpbm image has an invalid maximum value"
+    );
+    let pixel_count = calc_pixel_count(width, height)?;
+
+    let pixels = if channels == 0 {
+        let bits: Vec<bool> = if plain {
+            header.bytes[header.position..]
+                .iter()
+                .filter(|byte| matches!(byte, b'0' | b'1'))
+                .map(|&byte| byte == b'1')
+                .take(pixel_count)
+                .collect()
+        } else {
+            let data = header.raw_data();
+            let row_length = width.div_ceil(8);
+            ensure!(
+                data.len()
+                    >= row_length
+                        .checked_mul(height)
+                        .context("The Netpbm image is too large")?,
+                "The Netpbm image is truncated"
+            );
+            (0..pixel_count)
+                .map(|index| {
+                    let (row_index, column_index) = (index / width, index % width);
+                    data[row_index * row_length + column_index / 8] & (0x80 >> (column_index % 8))
+                        != 0
+                })
+                .collect()
+        };
+        ensure!(bits.len() == pixel_count, "The Netpbm image is truncated");
+        bits.into_iter()
+            .map(|black| if black { [0; 3] } else { WHITE })
+            .collect()
+    } else {
+        let sample_count = pixel_count
+            .checked_mul(channels)
+            .context("The Netpbm image is too large")?;
+        let samples: Vec<usize> = if plain {
+            (0..sample_count)
+                .map(|_| header.next_number())
+                .collect::<Result<_>>()?
+        } else {
+            let data = header.raw_data();
+            let sample_size = if max_value < 256 { 1 } else { 2 };
+            ensure!(
+                data.len()
+                    >= sample_count
+                        .checked_mul(sample_size)
+                        .context("The Netpbm image is too large")?,
+                "The Netpbm image is truncated"
+            );
+            data.chunks_exact(sample_size)
+                .take(sample_count)
+                .map(|sample| {
+                    sample
+                        .iter()
+                        .fold(0, |value, &byte| value * 256 + usize::from(byte))
+                })
+                .collect()
+        };
+        let scale = |sample: usize| {
+            u8::try_from(sample.min(max_value) * 255 / max_value).unwrap_or(u8::MAX)
+        };
+        samples
+            .chunks_exact(channels)
+            .map(|sample| match sample {
+                [gray] => [scale(*gray); 3],
+                _ => [scale(sample[0]), scale(sample[1]), scale(sample[2])],
+            })
+            .collect()
+    };
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+struct NetpbmHeader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> NetpbmHeader<'a> {
+    /// Reads the next token, skipping whitespace and comments, and stops just after it.
+    fn next_token(&mut self) -> Option<&'a [u8]> {
+        loop {
+            match self.bytes.get(self.position)? {
+                b'#' => {
+                    while self
+                        .bytes
+                        .get(self.position)
+                        .is_some_and(|&byte| byte != b'\n')
+                    {
+                        self.position += 1;
+                    }
+                }
+                byte if byte.is_ascii_whitespace() => self.position += 1,
+                _ => break,
+            }
+        }
+
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace() && *byte != b'#')
+        {
+            self.position += 1;
+        }
+        Some(&self.bytes[start..self.position])
+    }
+
+    /// The binary data of a raw image, after the single whitespace ending the header.
+    fn raw_data(&self) -> &'a [u8] {
+        self.bytes.get(self.position + 1..).unwrap_or_default()
+    }
+
+    fn next_number(&mut self) -> Result<usize> {
+        let token = self.next_token().context("The Netpbm image is truncated")?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .context("The Netpbm image has an invalid number")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ppm(width: usize, height: usize, pixels: impl Iterator<Item = Rgb>) -> Vec<u8> {
+        let mut bytes = format!("P6\n{width} {height}\n255\n").into_bytes();
+        bytes.extend(pixels.flatten());
+        bytes
+    }
+
+    #[test]
+    fn white_is_the_background() {
+        let black = [0; 3];
+        let red = [255, 0, 0];
+        let bytes = ppm(2, 2, [black, WHITE, red, black].into_iter());
+
+        let puzzle = Puzzle::from_image(&bytes).unwrap();
+        assert_eq!(puzzle.colors, ["#ffffff", "#000000", "#ff0000"]);
+        let goal = puzzle.get_goal().unwrap();
+        assert_eq!(goal.get_cell(0, 1).get_color_index(), Some(0));
+        assert_eq!(goal.get_cell(1, 0).get_color_index(), Some(2));
+    }
+
+    #[test]
+    fn many_colors_are_rejected() {
+        let pixels = (0..=255).map(|gray| [gray, gray, 0]);
+        let error = Puzzle::from_image(&ppm(256, 1, pixels)).unwrap_err();
+        assert!(error.to_string().contains("colors"), "{error}");
+    }
+}
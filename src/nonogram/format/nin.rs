@@ -0,0 +1,101 @@
+use super::{read_black_clues, write_black_clue, Puzzle};
+use anyhow::{ensure, Context, Result};
+use std::io::Write;
+
+impl Puzzle {
+    /// Reads a black and white puzzle in the `.nin` format: a line with the width and the height,
+    /// then the row clues and the column clues, one line of numbers each.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if some clues are missing, malformed or in excess, or if the puzzle is invalid.
+    pub fn from_nin(text: &str) -> Result<Self> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let size = lines.next().context("The input is empty")?;
+        let (width, height) = size
+            .split_once(char::is_whitespace)
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.trim().parse().ok()?)))
+            .with_context(|| format!("\"{size}\" is not a width and a height"))?;
+
+        let rows = read_black_clues(&mut lines, height).context("Invalid rows")?;
+        let columns = read_black_clues(&mut lines, width).context("Invalid columns")?;
+        ensure!(
+            lines.next().is_none(),
+            "The input has more clues than its {height} rows and {width} columns"
+        );
+
+        let puzzle = Self::black_and_white(rows, columns);
+        puzzle.validate()?;
+
+        Ok(puzzle)
+    }
+
+    /// Writes the puzzle in the `.nin` format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the puzzle is not black and white or if writing fails.
+    pub fn write_nin(&self, writer: &mut impl Write) -> Result<()> {
+        self.ensure_black_and_white("nin")?;
+
+        writeln!(writer, "{} {}", self.clues.1.len(), self.clues.0.len())?;
+        for line_clue in self.clues.0.iter().chain(&self.clues.1) {
+            write_black_clue(writer, line_clue, " ")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nonogram::validation::PuzzleError;
+
+    const FIXTURE: &str = "3 2\n1 1\n2\n1\n1\n2\n";
+
+    #[test]
+    fn round_trip_keeps_clues() {
+        let puzzle = Puzzle::from_nin(FIXTURE).unwrap();
+        let mut written = Vec::new();
+        puzzle.write_nin(&mut written).unwrap();
+        let reparsed = Puzzle::from_nin(std::str::from_utf8(&written).unwrap()).unwrap();
+
+        assert_eq!(reparsed.clues, puzzle.clues);
+    }
+
+    #[test]
+    fn header_is_the_width_then_the_height() {
+        let puzzle = Puzzle::from_nin(FIXTURE).unwrap();
+        assert_eq!(puzzle.clues.0.len(), 2);
+        assert_eq!(puzzle.clues.1.len(), 3);
+
+        let error = Puzzle::from_nin("3\n1 1\n2\n1\n1\n2\n").unwrap_err();
+        assert_eq!(error.to_string(), "\"3\" is not a width and a height");
+
+        let error = Puzzle::from_nin("\n\n").unwrap_err();
+        assert_eq!(error.to_string(), "The input is empty");
+    }
+
+    #[test]
+    fn clue_count_must_match_the_header() {
+        let error = Puzzle::from_nin(&FIXTURE.replace("3 2", "4 2")).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Invalid columns: Only 3 of the 4 clues are given"
+        );
+
+        let error = Puzzle::from_nin(&FIXTURE.replace("3 2", "2 2")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The input has more clues than its 2 rows and 2 columns"
+        );
+    }
+
+    #[test]
+    fn absurd_numbers_are_an_invalid_puzzle() {
+        let error =
+            Puzzle::from_nin("2 1\n18446744073709551615 18446744073709551615\n1\n1\n").unwrap_err();
+        assert!(error.downcast_ref::<PuzzleError>().is_some());
+    }
+}
@@ -0,0 +1,243 @@
+use super::{
+    super::solution::{Cell, Solution},
+    parse_black_clue, write_black_clue, LineClue, Puzzle,
+};
+use anyhow::{bail, ensure, Context, Result};
+use std::io::Write;
+use std::iter::Peekable;
+
+impl Puzzle {
+    /// Reads a black and white puzzle in Steve Simpson's `.non` format.
+    ///
+    /// The colors are `white` and `black`. If the file has a `goal` line,
+    /// it becomes the goal of the puzzle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if some clues are missing or malformed, or if the puzzle is invalid.
+    pub fn from_non(text: &str) -> Result<Self> {
+        let mut width = None;
+        let mut height = None;
+        let mut rows = None;
+        let mut columns = None;
+        let mut goal = None;
+
+        let mut lines = text.lines().map(str::trim).peekable();
+        while let Some(line) = lines.next() {
+            let (keyword, argument) = line
+                .split_once(char::is_whitespace)
+                .map_or((line, ""), |(keyword, argument)| (keyword, argument.trim()));
+            match keyword {
+                "width" => width = Some(parse_size("width", argument)?),
+                "height" => height = Some(parse_size("height", argument)?),
+                "rows" => rows = Some(read_clues(&mut lines, height).context("Invalid rows")?),
+                "columns" => {
+                    columns = Some(read_clues(&mut lines, width).context("Invalid columns")?);
+                }
+                "goal" => {
+                    let argument = if argument.is_empty() {
+                        lines.next().unwrap_or_default()
+                    } else {
+                        argument
+                    };
+                    goal = Some(argument.trim_matches('"').to_string());
+                }
+                // The other keywords, such as `title` or `copyright`, are only informative.
+                _ => {}
+            }
+        }
+
+        let rows = rows.context("The input has no rows")?;
+        let columns = columns.context("The input has no columns")?;
+        ensure!(
+            height.is_none_or(|height| height == rows.len()),
+            "The height does not match the number of rows"
+        );
+        ensure!(
+            width.is_none_or(|width| width == columns.len()),
+            "The width does not match the number of columns"
+        );
+
+        let mut puzzle = Self::black_and_white(rows, columns);
+        if let Some(goal) = goal {
+            puzzle.goal = Some(parse_goal(
+                &goal,
+                &puzzle.colors,
+                puzzle.clues.1.len(),
+                puzzle.clues.0.len(),
+            )?);
+        }
+        puzzle.validate()?;
+
+        Ok(puzzle)
+    }
+
+    /// Writes the puzzle in the `.non` format, with its goal if it has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the puzzle is not black and white or if writing fails.
+    pub fn write_non(&self, writer: &mut impl Write) -> Result<()> {
+        self.ensure_black_and_white("non")?;
+
+        writeln!(writer, "width {}", self.clues.1.len())?;
+        writeln!(writer, "height {}", self.clues.0.len())?;
+        for (keyword, line_clues) in [("rows", &self.clues.0), ("columns", &self.clues.1)] {
+            writeln!(writer)?;
+            writeln!(writer, "{keyword}")?;
+            for line_clue in line_clues {
+                write_black_clue(writer, line_clue, ",")?;
+            }
+        }
+
+        if let Some(goal) = self.goal.as_ref().and_then(Solution::to_non_goal) {
+            writeln!(writer)?;
+            writeln!(writer, "goal \"{goal}\"")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_size(keyword: &str, argument: &str) -> Result<usize> {
+    argument
+        .parse()
+        .with_context(|| format!("The {keyword} \"{argument}\" is not a number"))
+}
+
+/// Reads `count` clue lines, or as many as there are if the size is not known yet.
+fn read_clues<'a>(
+    lines: &mut Peekable<impl Iterator<Item = &'a str>>,
+    count: Option<usize>,
+) -> Result<Vec<LineClue>> {
+    let mut line_clues = Vec::new();
+    if let Some(count) = count {
+        for index in 0..count {
+            let line = lines
+                .next()
+                .with_context(|| format!("Only {index} of the {count} lines are given"))?;
+            line_clues.push(parse_black_clue(line)?);
+        }
+    } else {
+        while let Some(line) = lines.next_if(|line| {
+            !line.is_empty()
+                && line
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == ',' || c.is_whitespace())
+        }) {
+            line_clues.push(parse_black_clue(line)?);
+        }
+    }
+
+    Ok(line_clues)
+}
+
+fn parse_goal(goal: &str, colors: &[String], width: usize, height: usize) -> Result<Solution> {
+    ensure!(
+        goal.len() == width * height,
+        "The goal has {} cells, but the puzzle has {}",
+        goal.len(),
+        width * height
+    );
+
+    let mut cells = Vec::new();
+    for (row_index, row) in goal.as_bytes().chunks(width.max(1)).enumerate() {
+        let mut row_cells = Vec::new();
+        for &byte in row {
+            row_cells.push(match byte {
+                b'0' => Cell::Color(0),
+                b'1' => Cell::Color(1),
+                _ => bail!(
+                    "The goal has the character {:?} in row {}",
+                    char::from(byte),
+                    row_index + 1
+                ),
+            });
+        }
+        cells.push(row_cells);
+    }
+
+    Ok(Solution::new(colors.to_vec(), cells))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
+catalogue \"test\"
+width 3
+height 2
+
+rows
+1,1
+2
+
+columns
+1
+1
+2
+
+goal \"101011\"
+";
+
+    #[test]
+    fn round_trip_keeps_clues_and_goal() {
+        let puzzle = Puzzle::from_non(FIXTURE).unwrap();
+        let mut written = Vec::new();
+        puzzle.write_non(&mut written).unwrap();
+        let reparsed = Puzzle::from_non(std::str::from_utf8(&written).unwrap()).unwrap();
+
+        assert_eq!(reparsed.clues, puzzle.clues);
+        assert_eq!(reparsed.goal, puzzle.goal);
+        assert!(puzzle.goal.is_some());
+    }
+
+    #[test]
+    fn goal_may_be_on_the_next_line() {
+        let puzzle = Puzzle::from_non(&FIXTURE.replace("goal \"", "goal\n\"")).unwrap();
+        assert_eq!(puzzle.goal, Puzzle::from_non(FIXTURE).unwrap().goal);
+    }
+
+    #[test]
+    fn goal_must_fill_the_grid() {
+        let error = Puzzle::from_non(&FIXTURE.replace("101011", "10101")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The goal has 5 cells, but the puzzle has 6"
+        );
+
+        let error = Puzzle::from_non(&FIXTURE.replace("101011", "101021")).unwrap_err();
+        assert_eq!(error.to_string(), "The goal has the character '2' in row 2");
+    }
+
+    #[test]
+    fn rows_must_match_the_height() {
+        // The rows take the lines of the next section, up to the height.
+        let error = Puzzle::from_non(&FIXTURE.replace("height 2", "height 4")).unwrap_err();
+        assert!(
+            format!("{error:#}").starts_with("Invalid rows: \"columns\" is not a list of numbers")
+        );
+
+        // Before the height, the rows are read up to the first line that is not a clue.
+        let text = FIXTURE.replace("height 2\n", "") + "height 3\n";
+        let error = Puzzle::from_non(&text).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The height does not match the number of rows"
+        );
+    }
+
+    #[test]
+    fn missing_or_malformed_sections_are_errors() {
+        let text = &FIXTURE[..FIXTURE.find("columns").unwrap()];
+        let error = Puzzle::from_non(text).unwrap_err();
+        assert_eq!(error.to_string(), "The input has no columns");
+
+        let error = Puzzle::from_non(&FIXTURE.replace("width 3", "width three")).unwrap_err();
+        assert_eq!(error.to_string(), "The width \"three\" is not a number");
+
+        let error = Puzzle::from_non(&FIXTURE.replace("1,1\n", "1,x\n")).unwrap_err();
+        assert!(format!("{error:#}").starts_with("Invalid rows: \"1,x\" is not a list of numbers"));
+    }
+}
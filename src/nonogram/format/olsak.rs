@@ -0,0 +1,292 @@
+use super::{Description, LineClue, Puzzle};
+use anyhow::{bail, ensure, Context, Result};
+use std::io::Write;
+
+/// The identifiers of the colors after the background `0`, in the order they are written.
+const COLOR_IDS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+enum Section {
+    Comment,
+    Colors,
+    Rows,
+    Columns,
+}
+
+impl Puzzle {
+    /// Reads a puzzle in Mirek Olšák's `.g` format.
+    ///
+    /// Lines starting with `:` open a section: `: rows`, `: columns`, or a comment.
+    /// A `#d` line opens the color declarations, like `a:% #ff0000 red`, where `a` identifies
+    /// the color in the clues, `%` is its glyph and the rest its value, and `0` is the background.
+    /// A clue is a line of numbers followed by color identifiers, like `3a 1b`, or `0` if empty,
+    /// and a number without identifier has the first color after the background.
+    /// Without declarations, the colors are `white` and `black`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if some line is malformed, or if the puzzle is invalid.
+    pub fn from_olsak(text: &str) -> Result<Self> {
+        let mut declarations: Vec<(char, String)> = Vec::new();
+        let mut rows = Vec::new();
+        let mut columns = Vec::new();
+
+        let mut section = Section::Comment;
+        for line in text.lines().map(str::trim) {
+            if let Some(header) = line.strip_prefix(':') {
+                let header = header.trim().to_ascii_lowercase();
+                section = if header.starts_with("row") {
+                    Section::Rows
+                } else if header.starts_with("col") {
+                    Section::Columns
+                } else {
+                    Section::Comment
+                };
+            } else if line == "#d" {
+                section = Section::Colors;
+            } else {
+                match section {
+                    Section::Comment => {}
+                    Section::Colors if line.is_empty() => {}
+                    Section::Colors => {
+                        let (id, value) = parse_declaration(line)?;
+                        ensure!(
+                            declarations.iter().all(|(other, _)| *other != id),
+                            "The color {id} is declared twice"
+                        );
+                        declarations.push((id, value));
+                    }
+                    Section::Rows => rows.push(line),
+                    Section::Columns => columns.push(line),
+                }
+            }
+        }
+
+        if declarations.is_empty() {
+            declarations = vec![('0', "white".to_string()), ('a', "black".to_string())];
+        }
+        match declarations.iter().position(|(id, _)| *id == '0') {
+            Some(background_index) => {
+                let background = declarations.remove(background_index);
+                declarations.insert(0, background);
+            }
+            None => declarations.insert(0, ('0', "white".to_string())),
+        }
+        let (ids, colors): (Vec<char>, Vec<String>) = declarations.into_iter().unzip();
+
+        let puzzle = Self {
+            colors,
+            clues: (
+                parse_clues(&rows, &ids).context("Invalid rows")?,
+                parse_clues(&columns, &ids).context("Invalid columns")?,
+            ),
+            goal: None,
+        };
+        puzzle.validate()?;
+
+        Ok(puzzle)
+    }
+
+    /// Writes the puzzle in the `.g` format, identifying the colors by `a`, `b`, ...
+    /// and omitting the identifiers in black and white puzzles.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the puzzle has no colors or more colors than identifiers,
+    /// or if writing fails.
+    pub fn write_olsak(&self, writer: &mut impl Write) -> Result<()> {
+        let ids: Vec<char> = COLOR_IDS.chars().collect();
+        ensure!(
+            self.colors.len() <= ids.len() + 1,
+            "The g format allows at most {} colors",
+            ids.len() + 1
+        );
+        let get_id = |color_index: usize| ids[color_index - 1];
+        let background = self
+            .colors
+            .first()
+            .context("The puzzle has no background color")?;
+
+        writeln!(writer, "#d")?;
+        writeln!(writer, "   0:.   {background}")?;
+        for (color_index, color) in self.colors.iter().enumerate().skip(1) {
+            let id = get_id(color_index);
+            writeln!(writer, "   {id}:{id}   {color}")?;
+        }
+
+        for (header, line_clues) in [("rows", &self.clues.0), ("columns", &self.clues.1)] {
+            writeln!(writer, ": {header}")?;
+            for line_clue in line_clues {
+                if line_clue.is_empty() {
+                    writeln!(writer, "0")?;
+                    continue;
+                }
+
+                let descriptions: Vec<String> = line_clue
+                    .iter()
+                    .map(|description| {
+                        if self.colors.len() == 2 {
+                            description.number.to_string()
+                        } else {
+                            format!("{}{}", description.number, get_id(description.color_index))
+                        }
+                    })
+                    .collect();
+                writeln!(writer, "{}", descriptions.join(" "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses `id:glyph value`, where the value is `#rrggbb name`, `#rrggbb` or `name`.
+fn parse_declaration(line: &str) -> Result<(char, String)> {
+    let mut chars = line.chars();
+    let (Some(id), Some(':'), Some(_glyph)) = (chars.next(), chars.next(), chars.next()) else {
+        bail!("\"{line}\" is not a color declaration");
+    };
+
+    let value = chars.as_str().trim();
+    let value = match value.split_once(char::is_whitespace) {
+        Some((rgb, name)) if rgb.starts_with('#') => name.trim(),
+        _ => value,
+    };
+    ensure!(!value.is_empty(), "The color {id} has no value");
+
+    Ok((id, value.to_string()))
+}
+
+/// Parses the clue lines of a section, of which the blank lines at the end are ignored.
+fn parse_clues(lines: &[&str], ids: &[char]) -> Result<Vec<LineClue>> {
+    let length = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |index| index + 1);
+
+    lines[..length]
+        .iter()
+        .map(|line| parse_line_clue(line, ids))
+        .collect()
+}
+
+fn parse_line_clue(line: &str, ids: &[char]) -> Result<LineClue> {
+    if line.is_empty() || line == "0" {
+        return Ok(Vec::new());
+    }
+
+    line.split_whitespace()
+        .map(|token| {
+            let digits_end = token
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(token.len());
+            let (number, id) = token.split_at(digits_end);
+            let number = number
+                .parse()
+                .with_context(|| format!("\"{token}\" does not start with a number"))?;
+
+            let mut chars = id.chars();
+            let color_index = match (chars.next(), chars.next()) {
+                (None, _) => 1,
+                (Some(id), None) => ids
+                    .iter()
+                    .position(|other| *other == id)
+                    .with_context(|| format!("The color {id} is not declared"))?,
+                (Some(_), Some(_)) => bail!("\"{token}\" has more than one color"),
+            };
+
+            Ok(Description {
+                color_index,
+                number,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
+: a small colored puzzle
+#d
+   0:.   #ffffff white
+   a:%   #ff0000 red
+   b:#   black
+: rows
+1a 1b
+2b
+: columns
+1a
+1b
+2b
+";
+
+    #[test]
+    fn round_trip_keeps_colors_and_clues() {
+        let puzzle = Puzzle::from_olsak(FIXTURE).unwrap();
+        let mut written = Vec::new();
+        puzzle.write_olsak(&mut written).unwrap();
+        let reparsed = Puzzle::from_olsak(std::str::from_utf8(&written).unwrap()).unwrap();
+
+        assert_eq!(reparsed.colors, puzzle.colors);
+        assert_eq!(reparsed.clues, puzzle.clues);
+    }
+
+    #[test]
+    fn background_comes_first_and_plain_numbers_take_the_first_color() {
+        let text = "#d\na:% #ff0000 red\n0:. white\n: rows\n1 1a\n0\n: columns\n1\n0\n1\n\n";
+        let puzzle = Puzzle::from_olsak(text).unwrap();
+        assert_eq!(puzzle.colors, ["white", "red"]);
+        assert_eq!(
+            puzzle.clues.0,
+            [vec![Description::new(1, 1), Description::new(1, 1)], vec![]]
+        );
+        assert_eq!(puzzle.clues.1.len(), 3);
+
+        let puzzle = Puzzle::from_olsak("#d\nb:# black\n: rows\n1b\n: columns\n1b\n").unwrap();
+        assert_eq!(puzzle.colors, ["white", "black"]);
+    }
+
+    #[test]
+    fn color_declarations_must_be_well_formed() {
+        let error = Puzzle::from_olsak(&FIXTURE.replace("   b:#   black\n", "   b\n")).unwrap_err();
+        assert_eq!(error.to_string(), "\"b\" is not a color declaration");
+
+        let error =
+            Puzzle::from_olsak(&FIXTURE.replace("   b:#   black\n", "   b:#\n")).unwrap_err();
+        assert_eq!(error.to_string(), "The color b has no value");
+
+        let error = Puzzle::from_olsak(&FIXTURE.replace("   b:#", "   a:#")).unwrap_err();
+        assert_eq!(error.to_string(), "The color a is declared twice");
+    }
+
+    #[test]
+    fn clues_must_use_one_declared_color() {
+        let error = Puzzle::from_olsak(&FIXTURE.replace("2b\n:", "2z\n:")).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Invalid rows: The color z is not declared"
+        );
+
+        let error = Puzzle::from_olsak(&FIXTURE.replace("2b\n:", "2ab\n:")).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Invalid rows: \"2ab\" has more than one color"
+        );
+
+        let error = Puzzle::from_olsak(&FIXTURE.replace("1a\n1b", "a\n1b")).unwrap_err();
+        assert!(
+            format!("{error:#}").starts_with("Invalid columns: \"a\" does not start with a number")
+        );
+    }
+
+    #[test]
+    fn too_many_colors_cannot_be_written() {
+        let colors = (0..=COLOR_IDS.len() + 1)
+            .map(|index| index.to_string())
+            .collect();
+        let puzzle = Puzzle::new(colors, vec![vec![]], vec![vec![]]).unwrap();
+        let error = puzzle.write_olsak(&mut Vec::new()).unwrap_err();
+        assert_eq!(error.to_string(), "The g format allows at most 53 colors");
+    }
+}
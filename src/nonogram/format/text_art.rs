@@ -0,0 +1,119 @@
+use super::Puzzle;
+use anyhow::{bail, ensure, Context, Result};
+
+impl Puzzle {
+    /// Reads a picture drawn with one character per pixel and makes a puzzle of it.
+    ///
+    /// The picture may be preceded by a palette and a blank line. Each line of the palette,
+    /// like `r red`, gives a character and its color, the first one being the background.
+    /// Without palette, `.` is white and `#` is black.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the picture is missing, if the palette is malformed,
+    /// if the rows have different lengths,
+    /// if a character is not in the palette, or if the puzzle is invalid.
+    pub fn from_text_art(text: &str) -> Result<Self> {
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        let blocks: Vec<&[&str]> = lines
+            .split(|line| line.trim().is_empty())
+            .filter(|block| !block.is_empty())
+            .collect();
+
+        // A single block followed by a blank line is a palette whose picture is missing,
+        // unless it cannot be read as a palette.
+        let is_followed_by_blank = lines
+            .iter()
+            .skip_while(|line| line.trim().is_empty())
+            .any(|line| line.trim().is_empty());
+        let (palette, picture) = match blocks.as_slice() {
+            [] => bail!("The input has no picture"),
+            [block] if is_followed_by_blank && parse_palette(block).is_ok() => {
+                bail!("The palette is not followed by a picture")
+            }
+            [picture] => (
+                vec![('.', "white".to_string()), ('#', "black".to_string())],
+                *picture,
+            ),
+            [palette, picture] => (parse_palette(palette)?, *picture),
+            _ => bail!("The input should be a palette and a picture, separated by a blank line"),
+        };
+
+        let mut grid = Vec::new();
+        for (row_index, row) in picture.iter().enumerate() {
+            let row: Vec<usize> = row
+                .chars()
+                .map(|glyph| {
+                    palette
+                        .iter()
+                        .position(|(other, _)| *other == glyph)
+                        .with_context(|| {
+                            format!(
+                                "The character {glyph:?} of row {} is not in the palette",
+                                row_index + 1
+                            )
+                        })
+                })
+                .collect::<Result<_>>()?;
+            ensure!(
+                row.len() == picture[0].chars().count(),
+                "Row {} does not have the length of row 1",
+                row_index + 1
+            );
+            grid.push(row);
+        }
+
+        let colors = palette.into_iter().map(|(_, color)| color).collect();
+        Ok(Self::from_grid(colors, &grid)?)
+    }
+}
+
+fn parse_palette(lines: &[&str]) -> Result<Vec<(char, String)>> {
+    let mut palette: Vec<(char, String)> = Vec::new();
+    for line in lines {
+        let mut chars = line.chars();
+        let (Some(glyph), Some(separator)) = (chars.next(), chars.next()) else {
+            bail!("\"{line}\" is not a character followed by a color");
+        };
+        let color = chars.as_str().trim();
+        ensure!(
+            separator.is_whitespace() && !color.is_empty(),
+            "\"{line}\" is not a character followed by a color"
+        );
+        ensure!(
+            palette.iter().all(|(other, _)| *other != glyph),
+            "The character {glyph:?} is in the palette twice"
+        );
+        palette.push((glyph, color.to_string()));
+    }
+
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picture_with_palette_is_read() {
+        let puzzle = Puzzle::from_text_art(". white\nr red\n\nr.\n.r\n").unwrap();
+        assert_eq!(puzzle.colors, ["white", "red"]);
+        assert_eq!(puzzle.clues.0.len(), 2);
+        assert_eq!(puzzle.clues.1.len(), 2);
+    }
+
+    #[test]
+    fn palette_without_picture_is_an_error() {
+        let error = Puzzle::from_text_art("r red\n\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The palette is not followed by a picture"
+        );
+    }
+
+    #[test]
+    fn empty_input_is_an_error() {
+        let error = Puzzle::from_text_art("\n\n").unwrap_err();
+        assert_eq!(error.to_string(), "The input has no picture");
+    }
+}
@@ -0,0 +1,215 @@
+use super::super::{Description, LineClue, Puzzle};
+use anyhow::{anyhow, bail, Context, Result};
+use roxmltree::{Document, Node, ParsingOptions};
+
+impl Puzzle {
+    /// Reads a puzzle in the webpbn XML format, taking the first puzzle of the file.
+    ///
+    /// The background color becomes the color 0, and the colors are given as `#rrggbb`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the XML is malformed, lacks some clues, refers to an undefined color,
+    /// or describes an invalid puzzle.
+    pub fn from_webpbn(xml: &str) -> Result<Self> {
+        let options = ParsingOptions {
+            allow_dtd: true,
+            ..ParsingOptions::default()
+        };
+        let document =
+            Document::parse_with_options(xml, options).context("The XML input is malformed")?;
+        let puzzle_node = document
+            .descendants()
+            .find(|node| node.has_tag_name("puzzle"))
+            .context("The XML input has no <puzzle> element")?;
+
+        let colors = WebpbnColors::new(puzzle_node)?;
+        let rows = read_clues(puzzle_node, "rows", &colors)?;
+        let columns = read_clues(puzzle_node, "columns", &colors)?;
+
+        let puzzle = Self {
+            colors: colors.values,
+            clues: (rows, columns),
+            goal: None,
+        };
+        puzzle.validate()?;
+
+        Ok(puzzle)
+    }
+}
+
+struct WebpbnColors {
+    names: Vec<String>,
+    values: Vec<String>,
+    default_color: String,
+}
+
+impl WebpbnColors {
+    fn new(puzzle_node: Node) -> Result<Self> {
+        let background_color = puzzle_node.attribute("backgroundcolor").unwrap_or("white");
+        let default_color = puzzle_node.attribute("defaultcolor").unwrap_or("black");
+
+        let mut definitions: Vec<(String, String)> = vec![
+            ("white".to_string(), "#ffffff".to_string()),
+            ("black".to_string(), "#000000".to_string()),
+        ];
+        for node in puzzle_node
+            .children()
+            .filter(|node| node.has_tag_name("color"))
+        {
+            let name = node
+                .attribute("name")
+                .context("A <color> element has no name")?;
+            let value = parse_rgb(node.text().unwrap_or_default().trim())
+                .with_context(|| format!("The color \"{name}\" has an invalid value"))?;
+            match definitions.iter_mut().find(|(other, _)| other == name) {
+                Some(definition) => definition.1 = value,
+                None => definitions.push((name.to_string(), value)),
+            }
+        }
+
+        let background_index = definitions
+            .iter()
+            .position(|(name, _)| name == background_color)
+            .with_context(|| {
+                format!("The background color \"{background_color}\" is not defined")
+            })?;
+        let background = definitions.remove(background_index);
+        definitions.insert(0, background);
+
+        let (names, values) = definitions.into_iter().unzip();
+        Ok(Self {
+            names,
+            values,
+            default_color: default_color.to_string(),
+        })
+    }
+
+    fn get_index(&self, name: &str) -> Result<usize> {
+        self.names
+            .iter()
+            .position(|other| other == name)
+            .ok_or_else(|| anyhow!("The color \"{name}\" is not defined"))
+    }
+}
+
+fn read_clues(puzzle_node: Node, clue_type: &str, colors: &WebpbnColors) -> Result<Vec<LineClue>> {
+    let clues_node = puzzle_node
+        .children()
+        .find(|node| node.has_tag_name("clues") && node.attribute("type") == Some(clue_type))
+        .with_context(|| format!("The XML input has no <clues type=\"{clue_type}\"> element"))?;
+
+    let mut line_clues = Vec::new();
+    for line_node in clues_node
+        .children()
+        .filter(|node| node.has_tag_name("line"))
+    {
+        let mut line_clue = Vec::new();
+        for count_node in line_node
+            .children()
+            .filter(|node| node.has_tag_name("count"))
+        {
+            let color_name = count_node
+                .attribute("color")
+                .unwrap_or(&colors.default_color);
+            let number = count_node
+                .text()
+                .unwrap_or_default()
+                .trim()
+                .parse()
+                .with_context(|| {
+                    format!(
+                        "Line {} of the {clue_type} has an invalid count",
+                        line_clues.len() + 1
+                    )
+                })?;
+            line_clue.push(Description {
+                color_index: colors.get_index(color_name)?,
+                number,
+            });
+        }
+        line_clues.push(line_clue);
+    }
+
+    Ok(line_clues)
+}
+
+/// Converts `rgb` or `rrggbb` hexadecimal digits into `#rrggbb`.
+fn parse_rgb(text: &str) -> Result<String> {
+    let digits = text.strip_prefix('#').unwrap_or(text);
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("\"{text}\" is not hexadecimal");
+    }
+
+    match digits.len() {
+        3 => Ok(digits
+            .chars()
+            .flat_map(|c| [c, c])
+            .fold(String::from("#"), |mut value, c| {
+                value.push(c.to_ascii_lowercase());
+                value
+            })),
+        6 => Ok(format!("#{}", digits.to_ascii_lowercase())),
+        _ => bail!("\"{text}\" is not an RGB value"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE pbn SYSTEM "https://webpbn.com/pbn-0.3.dtd">
+<puzzleset>
+<puzzle type="grid" defaultcolor="black">
+<color name="white" char=".">fff</color>
+<color name="black" char="X">000000</color>
+<color name="red" char="r">#C00000</color>
+<clues type="columns">
+<line><count color="red">1</count></line>
+<line><count>1</count><count color="red">1</count></line>
+</clues>
+<clues type="rows">
+<line><count color="red">1</count><count>1</count></line>
+<line><count color="red">1</count></line>
+</clues>
+</puzzle>
+</puzzleset>
+"#;
+
+    #[test]
+    fn colors_and_clues_are_read() {
+        let puzzle = Puzzle::from_webpbn(FIXTURE).unwrap();
+        assert_eq!(puzzle.colors, ["#ffffff", "#000000", "#c00000"]);
+        assert_eq!(
+            puzzle.clues.0,
+            [
+                vec![Description::new(2, 1), Description::new(1, 1)],
+                vec![Description::new(2, 1)],
+            ]
+        );
+        assert_eq!(
+            puzzle.clues.1,
+            [
+                vec![Description::new(2, 1)],
+                vec![Description::new(1, 1), Description::new(2, 1)],
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_document_is_an_error() {
+        let error = Puzzle::from_webpbn("<puzzleset><puzzle><clues type=\"rows\">").unwrap_err();
+        assert_eq!(error.to_string(), "The XML input is malformed");
+    }
+
+    #[test]
+    fn missing_clues_are_an_error() {
+        let xml = FIXTURE.replace("type=\"columns\"", "type=\"cols\"");
+        let error = Puzzle::from_webpbn(&xml).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The XML input has no <clues type=\"columns\"> element"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+pub mod ansi;
+pub mod grid;
+pub mod svg;
+
+/// Converts a color given as `#rgb`, `#rrggbb` or a basic CSS name into its RGB components.
+fn parse_color(color: &str) -> Option<[u8; 3]> {
+    if let Some(digits) = color.strip_prefix('#') {
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let [_, red, green, blue] = u32::from_str_radix(digits, 16).ok()?.to_be_bytes();
+        return match digits.len() {
+            3 => Some([green, blue >> 4, blue & 0xf].map(|digit| digit * 0x11)),
+            6 => Some([red, green, blue]),
+            _ => None,
+        };
+    }
+
+    let rgb = match color.to_ascii_lowercase().as_str() {
+        "black" => [0x00, 0x00, 0x00],
+        "white" => [0xff, 0xff, 0xff],
+        "gray" | "grey" => [0x80, 0x80, 0x80],
+        "silver" => [0xc0, 0xc0, 0xc0],
+        "red" => [0xff, 0x00, 0x00],
+        "maroon" => [0x80, 0x00, 0x00],
+        "orange" => [0xff, 0xa5, 0x00],
+        "yellow" => [0xff, 0xff, 0x00],
+        "olive" => [0x80, 0x80, 0x00],
+        "lime" => [0x00, 0xff, 0x00],
+        "green" => [0x00, 0x80, 0x00],
+        "cyan" | "aqua" => [0x00, 0xff, 0xff],
+        "teal" => [0x00, 0x80, 0x80],
+        "blue" => [0x00, 0x00, 0xff],
+        "navy" => [0x00, 0x00, 0x80],
+        "magenta" | "fuchsia" => [0xff, 0x00, 0xff],
+        "purple" => [0x80, 0x00, 0x80],
+        "pink" => [0xff, 0xc0, 0xcb],
+        "brown" => [0xa5, 0x2a, 0x2a],
+        _ => return None,
+    };
+
+    Some(rgb)
+}
+
+/// Whether a text written over `color` should rather be white than black.
+fn is_dark(color: &str) -> bool {
+    parse_color(color).is_some_and(|[red, green, blue]| {
+        299 * u32::from(red) + 587 * u32::from(green) + 114 * u32::from(blue) < 128_000
+    })
+}
@@ -0,0 +1,110 @@
+use super::{
+    super::{
+        solution::{Cell, Solution},
+        Description, LineClue, Puzzle,
+    },
+    parse_color,
+};
+use std::io::{self, Write};
+
+const RESET: &str = "\x1b[0m";
+
+/// The glyph of the pixels whose color is not determined yet.
+const UNDETERMINED: &str = "░░";
+
+impl Solution {
+    /// Writes the grid with 24-bit ANSI colors, two characters per pixel,
+    /// and the clues of `clues` above and on the left if given.
+    ///
+    /// A pixel whose color is not known by name nor `#rrggbb` shows its color index instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn write_ansi(&self, clues: Option<&Puzzle>, writer: &mut impl Write) -> io::Result<()> {
+        let colors = self.get_colors();
+
+        let row_clues: Vec<(String, usize)> = clues
+            .map(|puzzle| {
+                puzzle
+                    .clues
+                    .0
+                    .iter()
+                    .map(|line_clue| paint_row_clue(colors, line_clue))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let margin = row_clues
+            .iter()
+            .map(|(_, width)| width + 1)
+            .max()
+            .unwrap_or(0);
+
+        if let Some(puzzle) = clues {
+            let column_clues = &puzzle.clues.1;
+            let depth = column_clues.iter().map(Vec::len).max().unwrap_or(0);
+            for level in 0..depth {
+                write!(writer, "{:margin$}", "")?;
+                for line_clue in column_clues {
+                    match (level + line_clue.len()).checked_sub(depth) {
+                        Some(index) => write!(writer, "{}", paint(colors, &line_clue[index], 2))?,
+                        None => write!(writer, "  ")?,
+                    }
+                }
+                writeln!(writer)?;
+            }
+        }
+
+        for (row_index, row) in self.get_rows().iter().enumerate() {
+            if let Some((text, width)) = row_clues.get(row_index) {
+                write!(
+                    writer,
+                    "{:padding$}{text} ",
+                    "",
+                    padding = margin - width - 1
+                )?;
+            }
+            for cell in row {
+                match cell {
+                    Cell::Color(color_index) => match parse_color(&colors[*color_index]) {
+                        Some([red, green, blue]) => {
+                            write!(writer, "\x1b[48;2;{red};{green};{blue}m  {RESET}")?;
+                        }
+                        None => write!(writer, "{color_index:>2}")?,
+                    },
+                    Cell::Candidates(_) => write!(writer, "{UNDETERMINED}")?,
+                }
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Paints the numbers of a row clue, returning the text and its width on the terminal.
+fn paint_row_clue(colors: &[String], line_clue: &LineClue) -> (String, usize) {
+    let numbers: Vec<String> = line_clue
+        .iter()
+        .map(|description| paint(colors, description, 0))
+        .collect();
+    let width = line_clue
+        .iter()
+        .map(|description| description.number.to_string().len() + 1)
+        .sum::<usize>()
+        .saturating_sub(1);
+
+    (numbers.join(" "), width)
+}
+
+/// Writes the number of a description right-aligned on `width` characters,
+/// in its color if the puzzle has several.
+fn paint(colors: &[String], description: &Description, width: usize) -> String {
+    let number = format!("{:>width$}", description.number);
+    match parse_color(&colors[description.color_index]) {
+        Some([red, green, blue]) if colors.len() > 2 => {
+            format!("\x1b[38;2;{red};{green};{blue}m{number}{RESET}")
+        }
+        _ => number,
+    }
+}
@@ -0,0 +1,62 @@
+use super::super::solution::{Cell, Solution};
+use serde_json::Value;
+use std::io::{self, Write};
+
+/// How `Solution::write_grid_json` writes the pixels whose color is not determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Undetermined {
+    /// As `null`.
+    Null,
+    /// As the list of their candidate color indices.
+    Candidates,
+}
+
+impl Solution {
+    /// Writes the grid as a JSON array of rows of color indices, one row per line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn write_grid_json(
+        &self,
+        undetermined: Undetermined,
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        writeln!(writer, "[")?;
+        for (row_index, row) in self.get_rows().iter().enumerate() {
+            let row: Vec<Value> = row
+                .iter()
+                .map(|cell| match (cell, undetermined) {
+                    (Cell::Color(color_index), _) => Value::from(*color_index),
+                    (Cell::Candidates(_), Undetermined::Null) => Value::Null,
+                    (Cell::Candidates(candidates), Undetermined::Candidates) => {
+                        Value::from(candidates.clone())
+                    }
+                })
+                .collect();
+            let separator = if row_index + 1 < self.get_height() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(writer, "  {}{separator}", Value::from(row))?;
+        }
+        writeln!(writer, "]")
+    }
+
+    /// The grid as the `goal` string of the `.non` format: one digit per pixel, row by row,
+    /// which is the color index, e.g. `1` for black.
+    ///
+    /// Returns `None` if some pixel is not determined or has a color index above 9.
+    #[must_use]
+    pub fn to_non_goal(&self) -> Option<String> {
+        self.get_rows()
+            .iter()
+            .flatten()
+            .map(|cell| {
+                let color_index = u32::try_from(cell.get_color_index()?).ok()?;
+                char::from_digit(color_index, 10)
+            })
+            .collect()
+    }
+}
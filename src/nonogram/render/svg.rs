@@ -0,0 +1,135 @@
+use super::{
+    super::{
+        solution::{Cell, Solution},
+        Description, Puzzle,
+    },
+    is_dark,
+};
+use std::io::{self, Write};
+
+const CELL_SIZE: usize = 20;
+
+impl Solution {
+    /// Writes the grid as an SVG picture in the colors of the puzzle, with the clues of `puzzle`
+    /// in the margins and the undetermined pixels hatched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn write_svg(&self, puzzle: &Puzzle, writer: &mut impl Write) -> io::Result<()> {
+        let (row_clues, column_clues) = &puzzle.clues;
+        let left = row_clues.iter().map(Vec::len).max().unwrap_or(0) * CELL_SIZE;
+        let top = column_clues.iter().map(Vec::len).max().unwrap_or(0) * CELL_SIZE;
+        let grid_width = self.get_width() * CELL_SIZE;
+        let grid_height = self.get_height() * CELL_SIZE;
+        let (width, height) = (left + grid_width + 1, top + grid_height + 1);
+
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )?;
+        writeln!(
+            writer,
+            r##"<defs><pattern id="undetermined" width="6" height="6" patternUnits="userSpaceOnUse" patternTransform="rotate(45)"><rect width="6" height="6" fill="#e0e0e0"/><line x1="0" y1="0" x2="0" y2="6" stroke="#909090" stroke-width="2"/></pattern></defs>"##
+        )?;
+        writeln!(
+            writer,
+            "<style>text {{ font-family: sans-serif; font-size: 12px; text-anchor: middle; dominant-baseline: central; }}</style>"
+        )?;
+
+        for (row_index, row) in self.get_rows().iter().enumerate() {
+            for (column_index, cell) in row.iter().enumerate() {
+                let fill = match cell {
+                    Cell::Color(color_index) => escape_xml(&self.get_colors()[*color_index]),
+                    Cell::Candidates(_) => "url(#undetermined)".to_string(),
+                };
+                writeln!(
+                    writer,
+                    r#"<rect x="{}" y="{}" width="{CELL_SIZE}" height="{CELL_SIZE}" fill="{fill}"/>"#,
+                    left + column_index * CELL_SIZE,
+                    top + row_index * CELL_SIZE
+                )?;
+            }
+        }
+
+        // Every fifth line is bold, as on printed puzzles.
+        let stroke_width = |index: usize| if index.is_multiple_of(5) { 2 } else { 1 };
+        for row_index in 0..=self.get_height() {
+            let y = top + row_index * CELL_SIZE;
+            writeln!(
+                writer,
+                r##"<line x1="{left}" y1="{y}" x2="{}" y2="{y}" stroke="#404040" stroke-width="{}"/>"##,
+                left + grid_width,
+                stroke_width(row_index)
+            )?;
+        }
+        for column_index in 0..=self.get_width() {
+            let x = left + column_index * CELL_SIZE;
+            writeln!(
+                writer,
+                r##"<line x1="{x}" y1="{top}" x2="{x}" y2="{}" stroke="#404040" stroke-width="{}"/>"##,
+                top + grid_height,
+                stroke_width(column_index)
+            )?;
+        }
+
+        for (row_index, line_clue) in row_clues.iter().enumerate() {
+            for (description_index, description) in line_clue.iter().enumerate() {
+                let x = left - (line_clue.len() - description_index) * CELL_SIZE;
+                self.write_description(writer, x, top + row_index * CELL_SIZE, description)?;
+            }
+        }
+        for (column_index, line_clue) in column_clues.iter().enumerate() {
+            for (description_index, description) in line_clue.iter().enumerate() {
+                let y = top - (line_clue.len() - description_index) * CELL_SIZE;
+                self.write_description(writer, left + column_index * CELL_SIZE, y, description)?;
+            }
+        }
+
+        writeln!(writer, "</svg>")
+    }
+
+    /// Writes a number of a clue in the square at (`x`, `y`), on its color if the puzzle has several.
+    fn write_description(
+        &self,
+        writer: &mut impl Write,
+        x: usize,
+        y: usize,
+        description: &Description,
+    ) -> io::Result<()> {
+        let text_fill = if self.get_colors().len() > 2 {
+            let color = &self.get_colors()[description.color_index];
+            writeln!(
+                writer,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x + 1,
+                y + 1,
+                CELL_SIZE - 2,
+                CELL_SIZE - 2,
+                escape_xml(color)
+            )?;
+            if is_dark(color) {
+                "white"
+            } else {
+                "black"
+            }
+        } else {
+            "black"
+        };
+
+        writeln!(
+            writer,
+            r#"<text x="{}" y="{}" fill="{text_fill}">{}</text>"#,
+            x + CELL_SIZE / 2,
+            y + CELL_SIZE / 2,
+            description.number
+        )
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
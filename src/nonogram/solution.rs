@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, stdout, Write};
+
+/// The state of a single pixel in a `Solution`.
+///
+/// It serializes as the color index itself, or as the list of candidate color indices.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Cell {
+    Color(usize),
+    Candidates(Vec<usize>),
+}
+
+impl Cell {
+    #[must_use]
+    pub fn get_color_index(&self) -> Option<usize> {
+        match self {
+            Cell::Color(color_index) => Some(*color_index),
+            Cell::Candidates(_) => None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_determined(&self) -> bool {
+        matches!(self, Cell::Color(_))
+    }
+}
+
+/// A grid of pixels together with the color palette of its puzzle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Solution {
+    height: usize,
+    width: usize,
+    colors: Vec<String>,
+    cells: Vec<Vec<Cell>>,
+}
+
+impl Solution {
+    /// Builds a solution from its rows of cells.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows do not all have the same length.
+    #[must_use]
+    pub fn new(colors: Vec<String>, cells: Vec<Vec<Cell>>) -> Self {
+        let height = cells.len();
+        let width = cells.first().map_or(0, Vec::len);
+        assert!(
+            cells.iter().all(|row| row.len() == width),
+            "All rows of a solution must have the same length"
+        );
+
+        Self {
+            height,
+            width,
+            colors,
+            cells,
+        }
+    }
+
+    #[must_use]
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub fn get_colors(&self) -> &[String] {
+        &self.colors
+    }
+
+    #[must_use]
+    pub fn get_cell(&self, row_index: usize, column_index: usize) -> &Cell {
+        &self.cells[row_index][column_index]
+    }
+
+    #[must_use]
+    pub fn get_rows(&self) -> &[Vec<Cell>] {
+        &self.cells
+    }
+
+    /// Returns `true` if every pixel has been resolved to a single color.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.cells.iter().flatten().all(Cell::is_determined)
+    }
+
+    /// Prints the grid, drawing `$$` for a pixel of the color 0,
+    /// blanks for other determined pixels and `..` for undetermined ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if writing to stdout fails, like `println!`.
+    pub fn show_blank_possibility(&self) {
+        self.write_blank_possibility(&mut stdout().lock())
+            .expect("Failed to write to stdout.");
+    }
+
+    /// Writes the grid drawn by `show_blank_possibility` into `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn write_blank_possibility(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(writer, " ")?;
+        for _ in 0..self.width {
+            write!(writer, "__")?;
+        }
+        writeln!(writer)?;
+
+        for row in &self.cells {
+            write!(writer, "|")?;
+            for cell in row {
+                match cell {
+                    Cell::Color(0) => write!(writer, "$$")?,
+                    Cell::Color(_) => write!(writer, "  ")?,
+                    Cell::Candidates(_) => write!(writer, "..")?,
+                }
+            }
+            writeln!(writer, "|")?;
+        }
+
+        write!(writer, " ")?;
+        for _ in 0..self.width {
+            write!(writer, "‾‾")?;
+        }
+        writeln!(writer)
+    }
+}
@@ -1,56 +1,106 @@
+mod count;
+mod heuristic;
 mod line_probability;
+mod options;
+mod priority;
+mod solutions;
 mod solve_resources;
+mod stats;
+
+pub use count::{check_uniqueness, count_solutions, SolutionCount, Uniqueness};
+pub use heuristic::{
+    BranchCandidate, BranchHeuristic, FirstUndeterminedHeuristic, MaxEntropyHeuristic,
+    MostConstrainedHeuristic, ProbabilityHeuristic, RandomHeuristic,
+};
+pub use options::{CancellationToken, SolveOptions};
+pub use priority::{
+    ChangedCellsPriority, FifoPriority, FreeSpacePriority, InitialLine, LineChange, LinePriority,
+    ProbabilityPriority,
+};
+pub use solutions::{solutions, Solutions};
+pub use stats::SolveStats;
 
 use crate::priority_queue::FxPriorityQueue;
-use cpu_time::ProcessTime;
 use fxhash::FxHashMap;
 use line_probability::LineProbability;
+use serde::Serialize;
 use solve_resources::SolveResources;
-use std::time::Instant;
-
-use super::*;
-
-pub fn solve(puzzle: &Puzzle) {
-    let cpu_start = ProcessTime::now();
-    let start = Instant::now();
-
-    let resources = SolveResources::new(puzzle);
-
-    let mut backtracks = 0;
-    let mut nlines = 0;
-    let mut layer_solver = LayerSolver::new(
-        None,
-        &resources,
-        vec![vec![resources.uncertain_memo; resources.width]; resources.height],
-    );
-    if let Some(priority_queue) = layer_solver.init(&mut nlines) {
-        let result = layer_solver.solve(priority_queue, &mut backtracks, &mut nlines);
-        let duration = start.elapsed();
-        let cpu_duration = cpu_start.elapsed();
-        match result {
-            SolveResult::FullySolved => {
-                layer_solver.show_blank_possibility();
-                println!("line_solves: {}", nlines);
-                println!("backtracks:  {}", backtracks);
-            }
-            SolveResult::PartiallySolved => {
-                println!("kya-");
-                layer_solver.show_blank_possibility();
-            }
-            SolveResult::Conflict => println!("nanndeyanenn"),
-        }
-        println!("cpu-time: {} sec", cpu_duration.as_secs_f64());
-        println!("    time: {} sec", duration.as_secs_f64());
-    } else {
-        let duration = start.elapsed();
-        let cpu_duration = cpu_start.elapsed();
-        println!("nanndeyanenn");
-        println!("cpu-time: {} sec", cpu_duration.as_secs_f64());
-        println!("    time: {} sec", duration.as_secs_f64());
+use stats::Stopwatch;
+use std::sync::Arc;
+
+use super::{
+    solution::{Cell, Solution},
+    LineClue, LineId, Puzzle,
+};
+
+/// The outcome of solving a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SolveResult {
+    /// The puzzle has exactly one solution and the grid holds it.
+    FullySolved,
+    /// The puzzle has more than one solution; the grid holds the cells common to all of them.
+    PartiallySolved,
+    /// The puzzle has no solution.
+    Conflict,
+    /// The search reached the timeout of `SolveOptions` before it could finish.
+    TimedOut,
+    /// The search was stopped by the cancellation token of `SolveOptions`.
+    Cancelled,
+    /// The search reached the maximum number of line solves or backtracks of `SolveOptions`.
+    BudgetExhausted,
+}
+
+impl SolveResult {
+    /// Whether the search stopped before it could finish, so that the grid is only partial.
+    #[must_use]
+    pub fn is_interrupted(self) -> bool {
+        matches!(
+            self,
+            SolveResult::TimedOut | SolveResult::Cancelled | SolveResult::BudgetExhausted
+        )
+    }
+}
+
+/// What `solve` returns: the result, the final grid and the statistics.
+///
+/// When the result is `Conflict`, `solution` holds the grid at the point the contradiction was found.
+/// When the search was interrupted, it holds the pixels deduced so far, which are common to all solutions.
+#[derive(Debug, Clone, Serialize)]
+pub struct SolveOutput {
+    pub result: SolveResult,
+    pub solution: Solution,
+    pub stats: SolveStats,
+}
+
+#[must_use]
+pub fn solve(puzzle: &Puzzle) -> SolveOutput {
+    solve_with(puzzle, &SolveOptions::default())
+}
+
+/// Solves the puzzle like `solve`, but stops early at the limits of `options`.
+#[must_use]
+pub fn solve_with(puzzle: &Puzzle, options: &SolveOptions) -> SolveOutput {
+    let stopwatch = Stopwatch::start();
+    let resources = SolveResources::new(puzzle, options);
+
+    let mut stats = SolveStats::default();
+    let mut layer_solver = LayerSolver::new_root(Arc::new(resources));
+    let result = match layer_solver.init(&mut stats) {
+        Ok(priority_queue) => layer_solver.solve(priority_queue, &mut stats),
+        Err(result) => result,
+    };
+    stopwatch.stop(&mut stats);
+
+    SolveOutput {
+        result,
+        solution: layer_solver.to_solution(&puzzle.colors),
+        stats,
     }
 }
 
-fn calc_priority(
+/// The ratio of the arrangements of `line` in which the pixel at `index` has one of
+/// `new_possible_colors`, among those in which it has one of them or of `new_impossible_colors`.
+fn calc_kept_ratio(
     line: &LineProbability,
     index: usize,
     new_possible_colors: usize,
@@ -59,13 +109,24 @@ fn calc_priority(
     let mut possible_num = 0;
     let mut impossible_num = 0;
     for new_possible_color in ColorIterator::new(new_possible_colors) {
-        possible_num += line.get_color_case(index, new_possible_color)
+        possible_num += line.get_color_case(index, new_possible_color);
     }
     for new_impossible_color in ColorIterator::new(new_impossible_colors) {
         impossible_num += line.get_color_case(index, new_impossible_color);
     }
 
-    (possible_num as f64 / (possible_num + impossible_num) as f64).ln()
+    #[allow(clippy::cast_precision_loss)]
+    (possible_num as f64 / (possible_num + impossible_num) as f64)
+}
+
+/// The colors of a pixel worth trying first: none if it is determined, and only the lowest one
+/// if it has two, since trying the other one leads to the same two cases.
+fn calc_branch_colors(pixel_memo: usize) -> usize {
+    match pixel_memo.count_ones() {
+        ..=1 => 0,
+        2 => 1 << pixel_memo.trailing_zeros(),
+        _ => pixel_memo,
+    }
 }
 
 impl Puzzle {
@@ -82,66 +143,56 @@ impl Puzzle {
     }
 }
 
-#[derive(Debug)]
+/// A state of the search: the possible colors of every pixel and the probabilities of every line.
+///
+/// A child layer starts as a copy of its parent, sharing the line probabilities
+/// until it solves the lines again.
+#[derive(Debug, Clone)]
 pub struct LayerSolver<'a> {
-    resources: &'a SolveResources<'a>,
-    parent: Option<&'a LayerSolver<'a>>,
-    grid: FxHashMap<PixelId, usize>,
-    grid_cache: Vec<Vec<usize>>,
-    line_probabilities: FxHashMap<LineId, LineProbability>,
-    line_cache: FxHashMap<LineId, &'a LineProbability>,
+    resources: Arc<SolveResources<'a>>,
+    grid: Vec<Vec<usize>>,
+    line_probabilities: FxHashMap<LineId, Arc<LineProbability>>,
+    depth: usize,
+    /// The number of lines queued so far on the way to this layer.
+    queue_sequence: u64,
 }
 
 impl<'a> LayerSolver<'a> {
-    fn new(
-        parent: Option<&'a LayerSolver<'a>>,
-        resources: &'a SolveResources<'a>,
-        grid_cache: Vec<Vec<usize>>,
-    ) -> Self {
+    fn new_root(resources: Arc<SolveResources<'a>>) -> Self {
+        let grid = vec![vec![resources.uncertain_memo; resources.width]; resources.height];
         Self {
             resources,
-            parent,
-            grid: Default::default(),
-            grid_cache,
-            line_probabilities: Default::default(),
-            line_cache: Default::default(),
+            grid,
+            line_probabilities: FxHashMap::default(),
+            depth: 0,
+            queue_sequence: 0,
         }
     }
 
+    fn new_child(
+        &self,
+        pixel_id: PixelId,
+        new_possible_colors: usize,
+        new_impossible_colors: usize,
+    ) -> (Self, FxPriorityQueue<LineId, Priority>) {
+        let mut layer_solver = self.clone();
+        layer_solver.depth += 1;
+        let priority_queue =
+            layer_solver.set_pixel_memo(pixel_id, new_possible_colors, new_impossible_colors);
+
+        (layer_solver, priority_queue)
+    }
+
     fn get_memo(&self, pixel_id: PixelId) -> usize {
-        self.grid
-            .get(&pixel_id)
-            .copied()
-            .unwrap_or_else(|| self.grid_cache[pixel_id.row_index][pixel_id.column_index])
-    }
-
-    fn cache_line(&mut self, line_id: LineId) -> Option<&LineProbability> {
-        match self.line_probabilities.get(&line_id) {
-            Some(line) => Some(line),
-            None => match self.line_cache.get(&line_id) {
-                Some(&line) => Some(line),
-                None => match self.get_ancestral_line(line_id) {
-                    Some(line) => {
-                        self.line_cache.insert(line_id, line);
-                        Some(line)
-                    }
-                    None => None,
-                },
-            },
-        }
+        self.grid[pixel_id.row_index][pixel_id.column_index]
     }
 
-    fn get_ancestral_line(&self, line_id: LineId) -> Option<&'a LineProbability> {
-        match self.parent {
-            Some(parent) => match parent.line_probabilities.get(&line_id) {
-                Some(line_probability) => Some(line_probability),
-                None => match parent.line_cache.get(&line_id) {
-                    Some(&line_probability) => Some(line_probability),
-                    None => parent.get_ancestral_line(line_id),
-                },
-            },
-            None => None,
-        }
+    fn set_memo(&mut self, pixel_id: PixelId, memo: usize) {
+        self.grid[pixel_id.row_index][pixel_id.column_index] = memo;
+    }
+
+    fn get_line(&self, line_id: LineId) -> Option<&LineProbability> {
+        self.line_probabilities.get(&line_id).map(AsRef::as_ref)
     }
 
     fn set_pixel_memo(
@@ -150,41 +201,81 @@ impl<'a> LayerSolver<'a> {
         new_possible_colors: usize,
         new_impossible_colors: usize,
     ) -> FxPriorityQueue<LineId, Priority> {
-        self.grid.insert(pixel_id, new_possible_colors);
-        let mut vec: Vec<(LineId, Priority)> = Default::default();
-        vec.push((
-            LineId::Row(pixel_id.row_index),
-            calc_priority(
-                self.cache_line(LineId::Row(pixel_id.row_index)).unwrap(),
-                pixel_id.column_index,
+        self.set_memo(pixel_id, new_possible_colors);
+        let mut priority_queue = FxPriorityQueue::new();
+        for (line_id, index) in [
+            (LineId::Row(pixel_id.row_index), pixel_id.column_index),
+            (LineId::Column(pixel_id.column_index), pixel_id.row_index),
+        ] {
+            self.queue_line(
+                &mut priority_queue,
+                line_id,
+                index,
                 new_possible_colors,
                 new_impossible_colors,
-            ),
-        ));
-        vec.push((
-            LineId::Column(pixel_id.column_index),
-            calc_priority(
-                self.cache_line(LineId::Column(pixel_id.column_index))
-                    .unwrap(),
-                pixel_id.row_index,
+            );
+        }
+
+        priority_queue
+    }
+
+    /// Queues `line_id` to be solved again, since its pixel at `pixel_index` lost
+    /// `new_impossible_colors`, with the priority given by the policy of the solve.
+    fn queue_line(
+        &mut self,
+        priority_queue: &mut FxPriorityQueue<LineId, Priority>,
+        line_id: LineId,
+        pixel_index: usize,
+        new_possible_colors: usize,
+        new_impossible_colors: usize,
+    ) {
+        let Some(line) = self.get_line(line_id) else {
+            return;
+        };
+        let change = LineChange {
+            line_id,
+            pixel_index,
+            kept_ratio: calc_kept_ratio(
+                line,
+                pixel_index,
                 new_possible_colors,
                 new_impossible_colors,
             ),
-        ));
+            sequence: self.queue_sequence,
+            grid: &self.grid,
+        };
+        self.queue_sequence += 1;
 
-        FxPriorityQueue::new_heapify(vec)
+        let priority = self
+            .resources
+            .line_priority
+            .requeue(priority_queue.get(&line_id).copied(), &change);
+        priority_queue.set_or_insert(line_id, priority);
     }
 
-    fn init(&mut self, nlines: &mut u128) -> Option<FxPriorityQueue<LineId, Priority>> {
-        let mut vec: Vec<(LineId, u128)> = Vec::new();
-        for i in 0..self.resources.height {
-            let line_id = LineId::Row(i);
-            vec.push((line_id, self.resources.get_binomial(line_id)));
-        }
-        for i in 0..self.resources.width {
-            let line_id = LineId::Column(i);
-            vec.push((line_id, self.resources.get_binomial(line_id)));
-        }
+    /// Solves every line once, in the order of the initial priorities of the policy of the solve.
+    ///
+    /// Returns the lines to solve again, or the result to stop with:
+    /// `Conflict` or the reason of an interruption.
+    fn init(
+        &mut self,
+        stats: &mut SolveStats,
+    ) -> Result<FxPriorityQueue<LineId, Priority>, SolveResult> {
+        let line_ids = (0..self.resources.height)
+            .map(LineId::Row)
+            .chain((0..self.resources.width).map(LineId::Column));
+        let vec: Vec<(LineId, Priority)> = line_ids
+            .zip(0..)
+            .map(|(line_id, sequence)| {
+                let line = InitialLine {
+                    line_id,
+                    slack: self.resources.get_free(line_id),
+                    arrangement_num: self.resources.get_binomial(line_id),
+                    sequence,
+                };
+                (line_id, self.resources.line_priority.initial(&line))
+            })
+            .collect();
 
         let mut priority_queue = FxPriorityQueue::new_heapify(vec);
         let mut result: FxPriorityQueue<LineId, Priority> = FxPriorityQueue::new();
@@ -192,13 +283,13 @@ impl<'a> LayerSolver<'a> {
         loop {
             match priority_queue.pop() {
                 Some(value) => {
-                    *nlines += 1;
-                    if !self.line_solve(value.0, &mut result) {
-                        return None;
+                    self.resources.limits.check_line_solve(stats)?;
+                    if !self.line_solve(value.0, &mut result, stats) {
+                        return Err(SolveResult::Conflict);
                     }
                 }
                 None => {
-                    return Some(result);
+                    return Ok(result);
                 }
             }
         }
@@ -208,30 +299,30 @@ impl<'a> LayerSolver<'a> {
         &mut self,
         line_id: LineId,
         priority_queue: &mut FxPriorityQueue<LineId, Priority>,
+        stats: &mut SolveStats,
     ) -> bool {
+        stats.line_solves += 1;
+
         let mut line_memo: Vec<usize> = Vec::new();
         for pixel_id in PixelIterator::new(line_id, self.resources.get_length(line_id)) {
             line_memo.push(self.get_memo(pixel_id));
         }
 
-        if !self
-            .line_probabilities
-            .entry(line_id)
-            .or_insert_with(|| {
-                LineProbability::new(
-                    self.resources,
-                    line_id,
-                    self.line_cache.get(&line_id).copied(),
-                )
-            })
-            .solve(&line_memo, self.resources.get_line_clue(line_id))
+        let line_probability = self.line_probabilities.entry(line_id).or_insert_with(|| {
+            stats.probability_recomputations += 1;
+            Arc::new(LineProbability::new(&self.resources, line_id))
+        });
+        if Arc::get_mut(line_probability).is_none() {
+            stats.probability_recomputations += 1;
+        }
+        if !Arc::make_mut(line_probability).solve(&line_memo, self.resources.get_line_clue(line_id))
         {
             return false;
         }
 
         for (pixel_index, &pixel_memo) in line_memo.iter().enumerate() {
             let mut new_impossible_colors = 0;
-            let line_probability = self.line_probabilities.get(&line_id).unwrap();
+            let line_probability = self.get_line(line_id).unwrap();
             for color_index in ColorIterator::new(pixel_memo) {
                 if line_probability.get_color_case(pixel_index, color_index) == 0 {
                     new_impossible_colors ^= 1 << color_index;
@@ -240,15 +331,15 @@ impl<'a> LayerSolver<'a> {
 
             if new_impossible_colors != 0 {
                 let new_possible_colors = pixel_memo ^ new_impossible_colors;
-                self.grid
-                    .insert(line_id.to_pixel_id(pixel_index), new_possible_colors);
+                self.set_memo(line_id.to_pixel_id(pixel_index), new_possible_colors);
                 let (oppo_line, oppo_index) = line_id.opposite(pixel_index);
-                if let Some(line) = self.cache_line(oppo_line) {
-                    priority_queue.add_or_insert(
-                        oppo_line,
-                        calc_priority(line, oppo_index, new_possible_colors, new_impossible_colors),
-                    );
-                }
+                self.queue_line(
+                    priority_queue,
+                    oppo_line,
+                    oppo_index,
+                    new_possible_colors,
+                    new_impossible_colors,
+                );
             }
         }
 
@@ -258,202 +349,278 @@ impl<'a> LayerSolver<'a> {
     fn solve(
         &mut self,
         mut priority_queue: FxPriorityQueue<LineId, Priority>,
-        backtracks: &mut u128,
-        nlines: &mut u128,
+        stats: &mut SolveStats,
     ) -> SolveResult {
-        loop {
-            if let Some((line_id, _)) = priority_queue.pop() {
-                *nlines += 1;
-                if !self.line_solve(line_id, &mut priority_queue) {
-                    return SolveResult::Conflict;
+        if let Err(result) = self.propagate(&mut priority_queue, stats) {
+            return result;
+        }
+        if self.resources.probing {
+            if let Err(result) = self.probe(stats) {
+                return result;
+            }
+        }
+
+        match self.find_branch_pixel() {
+            Some((pixel_id, color_index)) => {
+                if let Err(result) = self.resources.limits.check_branch(stats) {
+                    return result;
                 }
-            } else {
-                break;
+                self.count_backtrack(stats);
+                self.branch(pixel_id, color_index, stats)
             }
+            None => SolveResult::FullySolved,
         }
+    }
 
-        let mut min_value: Option<(f64, PixelId, usize)> = None;
+    /// Solves the lines of the queue until it is empty.
+    ///
+    /// Fails with `Conflict` or the reason of an interruption.
+    fn propagate(
+        &mut self,
+        priority_queue: &mut FxPriorityQueue<LineId, Priority>,
+        stats: &mut SolveStats,
+    ) -> Result<(), SolveResult> {
+        while let Some((line_id, _)) = priority_queue.pop() {
+            self.resources.limits.check_line_solve(stats)?;
+            if !self.line_solve(line_id, priority_queue, stats) {
+                return Err(SolveResult::Conflict);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tries once every color that `find_branch_pixel` could branch on, propagating each case
+    /// to its fixpoint, and keeps what both cases agree on.
+    ///
+    /// A case that ends in `Conflict` removes its colors for good. Otherwise, each pixel keeps
+    /// only the colors it has in one of the cases, as after branching on the pixel.
+    /// The branches below probe again, so one round per layer is enough.
+    /// Fails with `Conflict` or the reason of an interruption.
+    fn probe(&mut self, stats: &mut SolveStats) -> Result<(), SolveResult> {
         for row_index in 0..self.resources.height {
             for pixel_id in PixelIterator::new(LineId::Row(row_index), self.resources.width) {
-                let pixel_memo = self.get_memo(pixel_id);
-                match pixel_memo.count_ones() {
-                    ..=1 => continue,
-                    2 => {
-                        let color_index = pixel_memo.trailing_zeros() as usize;
-                        let value = self.calc_value(pixel_id, color_index);
-                        match min_value {
-                            Some(tuple) => {
-                                if value < tuple.0 {
-                                    min_value = Some((value, pixel_id, color_index))
-                                }
-                            }
-                            None => min_value = Some((value, pixel_id, color_index)),
-                        }
-                    }
-                    2.. => {
-                        for color_index in ColorIterator::new(pixel_memo) {
-                            let value = self.calc_value(pixel_id, color_index);
-                            match min_value {
-                                Some(tuple) => {
-                                    if value < tuple.0 {
-                                        min_value = Some((value, pixel_id, color_index))
-                                    }
-                                }
-                                None => min_value = Some((value, pixel_id, color_index)),
-                            }
-                        }
+                for color_index in ColorIterator::new(calc_branch_colors(self.get_memo(pixel_id))) {
+                    // An earlier trial of the same pixel may have removed the color.
+                    let pixel_memo = self.get_memo(pixel_id);
+                    if pixel_memo & (1 << color_index) == 0 || pixel_memo.count_ones() < 2 {
+                        continue;
                     }
+                    self.probe_pixel(pixel_id, color_index, stats)?;
                 }
             }
         }
 
-        match min_value {
-            Some((_, pixel_id, color_index)) => {
-                *backtracks += 1;
-                let result1;
-                let result2;
-                let grid1;
-                let grid2;
-                {
-                    let colors1 = 1 << color_index;
-                    let colors2 = self.get_memo(pixel_id) ^ colors1;
-
-                    let mut grid_cache = self.grid_cache.clone();
-                    for (&pixel_id, &memo) in self.grid.iter() {
-                        grid_cache[pixel_id.row_index][pixel_id.column_index] = memo;
-                    }
+        Ok(())
+    }
 
-                    let mut layer_solver1 =
-                        LayerSolver::new(Some(&self), self.resources, grid_cache.clone());
-                    let mut layer_solver2 =
-                        LayerSolver::new(Some(&self), self.resources, grid_cache);
+    /// Tries `color_index` at `pixel_id` and the other colors of the pixel.
+    fn probe_pixel(
+        &mut self,
+        pixel_id: PixelId,
+        color_index: usize,
+        stats: &mut SolveStats,
+    ) -> Result<(), SolveResult> {
+        let colors1 = 1 << color_index;
+        let colors2 = self.get_memo(pixel_id) ^ colors1;
+
+        let (mut layer_solver1, mut priority_queue1) = self.new_child(pixel_id, colors1, colors2);
+        match layer_solver1.propagate(&mut priority_queue1, stats) {
+            Err(SolveResult::Conflict) => {
+                // The trial layer still shares line probabilities with this one, which would
+                // then be copied instead of updated in place.
+                drop(layer_solver1);
+                let mut priority_queue = self.set_pixel_memo(pixel_id, colors2, colors1);
+                return self.propagate(&mut priority_queue, stats);
+            }
+            Err(result) => return Err(result),
+            Ok(()) => {}
+        }
 
-                    let priority_queue1 = layer_solver1.set_pixel_memo(pixel_id, colors1, colors2);
-                    let priority_queue2 = layer_solver2.set_pixel_memo(pixel_id, colors2, colors1);
+        let (mut layer_solver2, mut priority_queue2) = self.new_child(pixel_id, colors2, colors1);
+        match layer_solver2.propagate(&mut priority_queue2, stats) {
+            Err(SolveResult::Conflict) => {
+                layer_solver1.depth = self.depth;
+                *self = layer_solver1;
+                return Ok(());
+            }
+            Err(result) => return Err(result),
+            Ok(()) => {}
+        }
 
-                    result1 = layer_solver1.solve(priority_queue1, backtracks, nlines);
-                    result2 = layer_solver2.solve(priority_queue2, backtracks, nlines);
-                    grid1 = layer_solver1.grid;
-                    grid2 = layer_solver2.grid;
+        let mut priority_queue = FxPriorityQueue::new();
+        for row_index in 0..self.resources.height {
+            for pixel_id in PixelIterator::new(LineId::Row(row_index), self.resources.width) {
+                let pixel_memo = self.get_memo(pixel_id);
+                let new_possible_colors =
+                    layer_solver1.get_memo(pixel_id) | layer_solver2.get_memo(pixel_id);
+                if new_possible_colors == pixel_memo {
+                    continue;
                 }
 
-                match (result1, result2) {
-                    (SolveResult::FullySolved, SolveResult::FullySolved)
-                    | (SolveResult::FullySolved, SolveResult::PartiallySolved)
-                    | (SolveResult::PartiallySolved, SolveResult::FullySolved)
-                    | (SolveResult::PartiallySolved, SolveResult::PartiallySolved) => {
-                        let mut grid1 = grid1;
-                        for (pixel_id, &memo2) in grid2.iter() {
-                            if let Some(memo1) = grid1.get_mut(pixel_id) {
-                                *memo1 |= memo2;
-                            }
-                        }
-
-                        for (&pixel_id, &memo1) in grid1.iter() {
-                            if let Some(memo) = self.grid.get(&pixel_id) {
-                                if *memo == memo1 {
-                                    self.grid.remove(&pixel_id);
-                                } else {
-                                    self.grid.insert(pixel_id, memo1);
-                                }
-                            } else {
-                                self.grid.insert(pixel_id, memo1);
-                            }
-                        }
-
-                        SolveResult::PartiallySolved
-                    }
-                    (SolveResult::FullySolved, SolveResult::Conflict) => {
-                        for (&pixel_id, &memo1) in grid1.iter() {
-                            self.grid.insert(pixel_id, memo1);
-                        }
-
-                        SolveResult::FullySolved
-                    }
-                    (SolveResult::Conflict, SolveResult::FullySolved) => {
-                        for (&pixel_id, &memo2) in grid2.iter() {
-                            self.grid.insert(pixel_id, memo2);
-                        }
-
-                        SolveResult::FullySolved
-                    }
-                    (SolveResult::PartiallySolved, SolveResult::Conflict) => {
-                        for (&pixel_id, &memo1) in grid1.iter() {
-                            self.grid.insert(pixel_id, memo1);
-                        }
+                let new_impossible_colors = pixel_memo ^ new_possible_colors;
+                self.set_memo(pixel_id, new_possible_colors);
+                for (line_id, index) in [
+                    (LineId::Row(pixel_id.row_index), pixel_id.column_index),
+                    (LineId::Column(pixel_id.column_index), pixel_id.row_index),
+                ] {
+                    self.queue_line(
+                        &mut priority_queue,
+                        line_id,
+                        index,
+                        new_possible_colors,
+                        new_impossible_colors,
+                    );
+                }
+            }
+        }
+        drop(layer_solver1);
+        drop(layer_solver2);
+        self.propagate(&mut priority_queue, stats)
+    }
 
-                        SolveResult::PartiallySolved
-                    }
-                    (SolveResult::Conflict, SolveResult::PartiallySolved) => {
-                        for (&pixel_id, &memo2) in grid2.iter() {
-                            self.grid.insert(pixel_id, memo2);
-                        }
+    fn count_backtrack(&self, stats: &mut SolveStats) {
+        stats.backtracks += 1;
+        stats.max_depth = stats.max_depth.max(self.depth + 1);
+    }
 
-                        SolveResult::PartiallySolved
+    fn find_branch_pixel(&self) -> Option<(PixelId, usize)> {
+        let mut min_value: Option<(f64, PixelId, usize)> = None;
+        for row_index in 0..self.resources.height {
+            for pixel_id in PixelIterator::new(LineId::Row(row_index), self.resources.width) {
+                let color_memo = calc_branch_colors(self.get_memo(pixel_id));
+                for color_index in ColorIterator::new(color_memo) {
+                    let candidate = self.to_branch_candidate(pixel_id, color_index);
+                    let value = self.resources.branch_heuristic.score(&candidate);
+                    if min_value.is_none_or(|tuple| value < tuple.0) {
+                        min_value = Some((value, pixel_id, color_index));
                     }
-                    (SolveResult::Conflict, SolveResult::Conflict) => SolveResult::Conflict,
                 }
             }
-            None => SolveResult::FullySolved,
         }
-    }
 
-    fn calc_value(&mut self, pixel_id: PixelId, color_index: usize) -> f64 {
-        let row_probability = {
-            let line = self.cache_line(LineId::Row(pixel_id.row_index)).unwrap();
-            line.get_color_case(pixel_id.column_index, color_index) as f64
-                / line.get_painting_count() as f64
-        };
-        let column_probability = {
-            let line = self
-                .cache_line(LineId::Column(pixel_id.column_index))
-                .unwrap();
-            line.get_color_case(pixel_id.row_index, color_index) as f64
-                / line.get_painting_count() as f64
-        };
-
-        (row_probability - 0.5) * (column_probability - 0.5)
+        min_value.map(|(_, pixel_id, color_index)| (pixel_id, color_index))
     }
 
-    fn show_blank_possibility(&mut self) {
-        print!(" ");
-        for _ in 0..self.resources.width {
-            print!("__");
-        }
-        println!();
+    fn branch(
+        &mut self,
+        pixel_id: PixelId,
+        color_index: usize,
+        stats: &mut SolveStats,
+    ) -> SolveResult {
+        let colors1 = 1 << color_index;
+        let colors2 = self.get_memo(pixel_id) ^ colors1;
+
+        let (mut layer_solver1, priority_queue1) = self.new_child(pixel_id, colors1, colors2);
+        let (mut layer_solver2, priority_queue2) = self.new_child(pixel_id, colors2, colors1);
+
+        let (result1, result2) = if self.depth < self.resources.parallel_depth {
+            let (mut stats1, mut stats2) = (*stats, *stats);
+            let (result1, result2) = rayon::join(
+                || layer_solver1.solve(priority_queue1, &mut stats1),
+                || layer_solver2.solve(priority_queue2, &mut stats2),
+            );
+            stats.add_branches(&stats1, &stats2);
+            (result1, result2)
+        } else {
+            let result1 = layer_solver1.solve(priority_queue1, stats);
+            if result1.is_interrupted() {
+                return result1;
+            }
+            (result1, layer_solver2.solve(priority_queue2, stats))
+        };
 
-        for row_index in 0..self.resources.height {
-            print!("|");
-            for pixel_id in PixelIterator::new(LineId::Row(row_index), self.resources.width) {
-                let memo = self.get_memo(pixel_id);
-                if memo == 1 {
-                    print!("$$");
-                } else if memo.count_ones() == 1 {
-                    print!("  ");
-                } else {
-                    print!("..");
+        // An interrupted branch leaves this layer as it is, since the pixels of the other branch
+        // are not known to be common to all solutions, unless the other branch has no solution.
+        match (result1, result2) {
+            (result, SolveResult::Conflict) if result.is_interrupted() => {
+                self.grid = layer_solver1.grid;
+                result
+            }
+            (SolveResult::Conflict, result) if result.is_interrupted() => {
+                self.grid = layer_solver2.grid;
+                result
+            }
+            (result, _) | (_, result) if result.is_interrupted() => result,
+            (
+                SolveResult::FullySolved | SolveResult::PartiallySolved,
+                SolveResult::FullySolved | SolveResult::PartiallySolved,
+            ) => {
+                for (row, (row1, row2)) in self
+                    .grid
+                    .iter_mut()
+                    .zip(layer_solver1.grid.iter().zip(&layer_solver2.grid))
+                {
+                    for (memo, (memo1, memo2)) in row.iter_mut().zip(row1.iter().zip(row2)) {
+                        *memo = memo1 | memo2;
+                    }
                 }
+
+                SolveResult::PartiallySolved
+            }
+            (
+                result @ (SolveResult::FullySolved | SolveResult::PartiallySolved),
+                SolveResult::Conflict,
+            ) => {
+                self.grid = layer_solver1.grid;
+                result
+            }
+            (
+                SolveResult::Conflict,
+                result @ (SolveResult::FullySolved | SolveResult::PartiallySolved),
+            ) => {
+                self.grid = layer_solver2.grid;
+                result
             }
-            println!("|");
+            (SolveResult::Conflict, SolveResult::Conflict) => SolveResult::Conflict,
+            _ => unreachable!("The interrupted branches are handled above"),
         }
+    }
 
-        print!(" ");
-        for _ in 0..self.resources.width {
-            print!("‾‾");
+    #[allow(clippy::cast_precision_loss)]
+    fn to_branch_candidate(&self, pixel_id: PixelId, color_index: usize) -> BranchCandidate {
+        let row = self.get_line(LineId::Row(pixel_id.row_index)).unwrap();
+        let column = self
+            .get_line(LineId::Column(pixel_id.column_index))
+            .unwrap();
+
+        BranchCandidate {
+            row_index: pixel_id.row_index,
+            column_index: pixel_id.column_index,
+            color_index,
+            color_num: self.get_memo(pixel_id).count_ones() as usize,
+            row_probability: row.get_color_case(pixel_id.column_index, color_index) as f64
+                / row.get_painting_count() as f64,
+            column_probability: column.get_color_case(pixel_id.row_index, color_index) as f64
+                / column.get_painting_count() as f64,
+            row_painting_count: row.get_painting_count(),
+            column_painting_count: column.get_painting_count(),
         }
-        println!();
     }
-}
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
-enum LineId {
-    Row(usize),
-    Column(usize),
+    fn to_solution(&self, colors: &[String]) -> Solution {
+        let cells = (0..self.resources.height)
+            .map(|row_index| {
+                PixelIterator::new(LineId::Row(row_index), self.resources.width)
+                    .map(|pixel_id| {
+                        let memo = self.get_memo(pixel_id);
+                        if memo.is_power_of_two() {
+                            Cell::Color(memo.trailing_zeros() as usize)
+                        } else {
+                            Cell::Candidates(ColorIterator::new(memo).collect())
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Solution::new(colors.to_vec(), cells)
+    }
 }
 
 impl LineId {
-    fn to_pixel_id(&self, index: usize) -> PixelId {
-        match *self {
+    fn to_pixel_id(self, index: usize) -> PixelId {
+        match self {
             LineId::Row(row_index) => PixelId {
                 row_index,
                 column_index: index,
@@ -465,8 +632,8 @@ impl LineId {
         }
     }
 
-    fn opposite(&self, pixel_index: usize) -> (LineId, usize) {
-        match *self {
+    fn opposite(self, pixel_index: usize) -> (LineId, usize) {
+        match self {
             LineId::Row(row_index) => (LineId::Column(pixel_index), row_index),
             LineId::Column(column_index) => (LineId::Row(pixel_index), column_index),
         }
@@ -546,8 +713,78 @@ impl Iterator for ColorIterator {
 
 type Priority = f64;
 
-enum SolveResult {
-    FullySolved,
-    PartiallySolved,
-    Conflict,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Puzzle {
+        let colors = vec!["white".to_string(), "black".to_string()];
+        Puzzle::from_grid(colors, &[vec![1, 0], vec![0, 1]]).unwrap()
+    }
+
+    #[test]
+    fn ambiguous_puzzle_keeps_every_cell_undetermined() {
+        let puzzle = checkerboard();
+
+        let output = solve(&puzzle);
+        assert_eq!(output.result, SolveResult::PartiallySolved);
+        for cell in output.solution.get_rows().iter().flatten() {
+            assert_eq!(*cell, Cell::Candidates(vec![0, 1]));
+        }
+
+        let solution_count = count_solutions(&puzzle, None);
+        assert_eq!(solution_count.count, 2);
+        assert_ne!(solution_count.witnesses[0], solution_count.witnesses[1]);
+        for witness in &solution_count.witnesses {
+            assert!(witness.is_complete());
+            assert!(puzzle.check(witness).is_empty());
+        }
+    }
+
+    #[test]
+    fn unique_puzzle_is_fully_solved_into_its_goal() {
+        let colors = vec!["white".to_string(), "black".to_string()];
+        let puzzle = Puzzle::from_grid(colors, &[vec![1, 1], vec![0, 1]]).unwrap();
+
+        let output = solve(&puzzle);
+        assert_eq!(output.result, SolveResult::FullySolved);
+        assert_eq!(Some(&output.solution), puzzle.get_goal());
+        assert!(puzzle.check(&output.solution).is_empty());
+    }
+
+    #[test]
+    fn probing_keeps_the_solution_and_saves_backtracks() {
+        let rows = [
+            "..#.#.###.##",
+            "..##...###..",
+            "...#.##.#...",
+            "#..#...###.#",
+            "#.#....#.#..",
+            "...###.#####",
+            ".##..#.##.#.",
+            "#..#.###.##.",
+            ".#.#.##.#...",
+            ".#######....",
+        ];
+        let grid: Vec<Vec<usize>> = rows
+            .iter()
+            .map(|row| row.chars().map(|c| usize::from(c == '#')).collect())
+            .collect();
+        let colors = vec!["white".to_string(), "black".to_string()];
+        let puzzle = Puzzle::from_grid(colors, &grid).unwrap();
+
+        let plain = solve_with(&puzzle, &SolveOptions::default());
+        let probed = solve_with(
+            &puzzle,
+            &SolveOptions {
+                probing: true,
+                ..SolveOptions::default()
+            },
+        );
+        assert!(plain.stats.backtracks > 0);
+        assert_eq!(probed.result, SolveResult::FullySolved);
+        assert_eq!(probed.result, plain.result);
+        assert_eq!(probed.solution, plain.solution);
+        assert!(probed.stats.backtracks <= plain.stats.backtracks);
+    }
 }
@@ -0,0 +1,59 @@
+use super::{solutions, Puzzle, Solution, SolveStats};
+
+/// Whether a puzzle has no solution, exactly one, or more than one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Uniqueness {
+    NoSolution,
+    Unique(Solution),
+    /// Two distinct solutions witnessing that the puzzle is ambiguous.
+    Multiple(Solution, Solution),
+}
+
+/// The number of solutions found by `count_solutions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolutionCount {
+    pub count: usize,
+    /// `true` if the search stopped at the limit, so that there may be more solutions.
+    pub limit_reached: bool,
+    /// The first two solutions found.
+    pub witnesses: Vec<Solution>,
+    pub stats: SolveStats,
+}
+
+/// Counts the solutions of the puzzle, stopping once `limit` of them have been found.
+#[must_use]
+pub fn count_solutions(puzzle: &Puzzle, limit: Option<usize>) -> SolutionCount {
+    let mut solutions = solutions(puzzle);
+    let mut count = 0;
+    let mut witnesses = Vec::new();
+
+    while limit != Some(count) {
+        match solutions.next() {
+            Some(solution) => {
+                count += 1;
+                if witnesses.len() < 2 {
+                    witnesses.push(solution);
+                }
+            }
+            None => break,
+        }
+    }
+
+    SolutionCount {
+        count,
+        limit_reached: limit == Some(count),
+        witnesses,
+        stats: solutions.get_stats(),
+    }
+}
+
+/// Finds out whether the puzzle has a unique solution.
+#[must_use]
+pub fn check_uniqueness(puzzle: &Puzzle) -> Uniqueness {
+    let mut witnesses = count_solutions(puzzle, Some(2)).witnesses.into_iter();
+    match (witnesses.next(), witnesses.next()) {
+        (None, _) => Uniqueness::NoSolution,
+        (Some(solution), None) => Uniqueness::Unique(solution),
+        (Some(solution1), Some(solution2)) => Uniqueness::Multiple(solution1, solution2),
+    }
+}
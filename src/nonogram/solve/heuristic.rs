@@ -0,0 +1,118 @@
+use std::fmt;
+
+/// A pixel and one of its candidate colors that the solver may branch on,
+/// with what the solver knows about it.
+#[derive(Debug, Clone, Copy)]
+pub struct BranchCandidate {
+    pub row_index: usize,
+    pub column_index: usize,
+    pub color_index: usize,
+    /// The number of colors the pixel may still have.
+    pub color_num: usize,
+    /// The ratio of the arrangements of the row in which the pixel has the color.
+    pub row_probability: f64,
+    /// The ratio of the arrangements of the column in which the pixel has the color.
+    pub column_probability: f64,
+    /// The number of arrangements of the row that are still possible.
+    pub row_painting_count: u128,
+    /// The number of arrangements of the column that are still possible.
+    pub column_painting_count: u128,
+}
+
+/// Chooses the pixel and the color to try first when line solving alone cannot go further.
+///
+/// The solver scores every candidate and branches on the one with the lowest score,
+/// the first one in row-major order on ties. A pixel with only two candidate colors is offered
+/// with the lower one only, since both branches of either choice are the same.
+pub trait BranchHeuristic: fmt::Debug + Send + Sync {
+    fn score(&self, candidate: &BranchCandidate) -> f64;
+}
+
+/// Prefers the candidates whose row and column disagree the most about the color,
+/// and then those about which both are the most sure. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProbabilityHeuristic;
+
+impl BranchHeuristic for ProbabilityHeuristic {
+    fn score(&self, candidate: &BranchCandidate) -> f64 {
+        (candidate.row_probability - 0.5) * (candidate.column_probability - 0.5)
+    }
+}
+
+/// Prefers the pixels with the fewest candidate colors, and then those whose row and column
+/// have the fewest arrangements left.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MostConstrainedHeuristic;
+
+impl BranchHeuristic for MostConstrainedHeuristic {
+    #[allow(clippy::cast_precision_loss)]
+    fn score(&self, candidate: &BranchCandidate) -> f64 {
+        // The logarithms of the counts of arrangements are far below this factor.
+        let arrangements = (candidate.row_painting_count as f64).ln()
+            + (candidate.column_painting_count as f64).ln();
+        candidate.color_num as f64 * 1e6 + arrangements
+    }
+}
+
+/// Prefers the candidates whose color is the most uncertain, that is whose mean probability
+/// over the row and the column has the highest binary entropy.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxEntropyHeuristic;
+
+impl BranchHeuristic for MaxEntropyHeuristic {
+    fn score(&self, candidate: &BranchCandidate) -> f64 {
+        let probability = f64::midpoint(candidate.row_probability, candidate.column_probability);
+        let entropy = [probability, 1.0 - probability]
+            .into_iter()
+            .filter(|&p| p > 0.0)
+            .map(|p| -p * p.log2())
+            .sum::<f64>();
+        -entropy
+    }
+}
+
+/// Takes the first undetermined pixel in row-major order and its lowest candidate color.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstUndeterminedHeuristic;
+
+impl BranchHeuristic for FirstUndeterminedHeuristic {
+    fn score(&self, _candidate: &BranchCandidate) -> f64 {
+        0.0
+    }
+}
+
+/// Takes a candidate at random. The same seed always gives the same choices,
+/// so that a search can be repeated.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomHeuristic {
+    pub seed: u64,
+}
+
+impl RandomHeuristic {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl BranchHeuristic for RandomHeuristic {
+    #[allow(clippy::cast_precision_loss)]
+    fn score(&self, candidate: &BranchCandidate) -> f64 {
+        // A SplitMix64 hash of the seed and the candidate, so that no state is shared
+        // between the branches explored in parallel.
+        let mut hash = self.seed;
+        for value in [
+            candidate.row_index,
+            candidate.column_index,
+            candidate.color_index,
+        ] {
+            hash = hash
+                .wrapping_add(value as u64)
+                .wrapping_add(0x9e37_79b9_7f4a_7c15);
+            hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            hash ^= hash >> 31;
+        }
+        hash as f64
+    }
+}
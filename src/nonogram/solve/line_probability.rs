@@ -1,6 +1,6 @@
-use super::*;
+use super::{LineClue, LineId, SolveResources};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LineProbability {
     color_cases: Vec<Vec<u128>>,
     painting_count: u128,
@@ -8,47 +8,38 @@ pub struct LineProbability {
 }
 
 impl LineProbability {
-    pub fn new(
-        resources: &SolveResources,
-        line_id: LineId,
-        parent: Option<&LineProbability>,
-    ) -> Self {
+    pub fn new(resources: &SolveResources, line_id: LineId) -> Self {
         Self {
             color_cases: vec![vec![0; resources.color_num]; resources.get_length(line_id)],
             painting_count: 0,
-            description_notes: match parent {
-                Some(parent) => parent.description_notes.clone(),
-                None => {
-                    let free = resources.get_free(line_id);
-                    let line_clue = resources.get_line_clue(line_id);
-                    let mut description_notes: Vec<DescriptionNote> = Default::default();
-
-                    for clue_index in 0..line_clue.len() {
-                        if clue_index == 0 {
-                            description_notes.push(DescriptionNote::new(free, 0));
-                        } else {
-                            description_notes.push(DescriptionNote::new(free, {
-                                let pre_index = clue_index - 1;
-                                description_notes[pre_index].min_index
-                                    + line_clue[pre_index].number
-                                    + if line_clue[pre_index].color_index
-                                        == line_clue[clue_index].color_index
-                                    {
-                                        1
-                                    } else {
-                                        0
-                                    }
-                            }));
-                        }
+            description_notes: {
+                let free = resources.get_free(line_id);
+                let line_clue = resources.get_line_clue(line_id);
+                let mut description_notes: Vec<DescriptionNote> = Vec::new();
+
+                for clue_index in 0..line_clue.len() {
+                    if clue_index == 0 {
+                        description_notes.push(DescriptionNote::new(free, 0));
+                    } else {
+                        description_notes.push(DescriptionNote::new(free, {
+                            let pre_index = clue_index - 1;
+                            description_notes[pre_index].min_index
+                                + line_clue[pre_index].number
+                                + usize::from(
+                                    line_clue[pre_index].color_index
+                                        == line_clue[clue_index].color_index,
+                                )
+                        }));
                     }
-                    description_notes
                 }
+                description_notes
             },
         }
     }
 
-    pub fn solve(&mut self, line_memo: &Vec<usize>, line_clue: &LineClue) -> bool {
-        if line_clue.len() == 0 {
+    #[allow(clippy::too_many_lines)]
+    pub fn solve(&mut self, line_memo: &[usize], line_clue: &LineClue) -> bool {
+        if line_clue.is_empty() {
             for (index, memo) in line_memo.iter().enumerate() {
                 if memo & 1 == 0 {
                     return false;
@@ -72,30 +63,25 @@ impl LineProbability {
                                 break;
                             }
                         }
-                    } else {
-                        if line_memo[min_index + place_index + description.number - 1]
-                            & (1 << description.color_index)
-                            == 0
-                        {
+                    } else if line_memo[min_index + place_index + description.number - 1]
+                        & (1 << description.color_index)
+                        == 0
+                    {
+                        self.description_notes[clue_index].segments[place_index].block_states =
+                            BlockStates::Blocked(description.number - 1);
+                    } else if let BlockStates::Blocked(i) =
+                        self.description_notes[clue_index].segments[place_index - 1].block_states
+                    {
+                        if i != 0 {
                             self.description_notes[clue_index].segments[place_index].block_states =
-                                BlockStates::Blocked(description.number - 1);
-                        } else {
-                            if let BlockStates::Blocked(i) = self.description_notes[clue_index]
-                                .segments[place_index - 1]
-                                .block_states
-                            {
-                                if i != 0 {
-                                    self.description_notes[clue_index].segments[place_index]
-                                        .block_states = BlockStates::Blocked(i - 1)
-                                }
-                            }
+                                BlockStates::Blocked(i - 1);
                         }
                     }
 
                     self.description_notes[clue_index].segments[place_index].left_cases = 0;
 
                     if is_first_clue && is_first_place {
-                        self.description_notes[clue_index].segments[place_index].left_cases = 1
+                        self.description_notes[clue_index].segments[place_index].left_cases = 1;
                     } else {
                         let is_blank_possible = line_memo[min_index + place_index - 1] & 1 == 1;
 
@@ -110,8 +96,7 @@ impl LineProbability {
                                 .block_states
                                 == BlockStates::Open
                             && (is_blank_possible
-                                || !(line_clue[clue_index - 1].color_index
-                                    == description.color_index))
+                                || line_clue[clue_index - 1].color_index != description.color_index)
                         {
                             self.description_notes[clue_index].segments[place_index].left_cases +=
                                 self.description_notes[clue_index - 1].segments[place_index]
@@ -147,8 +132,7 @@ impl LineProbability {
                                 .block_states
                                 == BlockStates::Open
                             && (is_blank_possible
-                                || !(line_clue[clue_index + 1].color_index
-                                    == description.color_index))
+                                || line_clue[clue_index + 1].color_index != description.color_index)
                         {
                             self.description_notes[clue_index].segments[place_index].right_cases +=
                                 self.description_notes[clue_index + 1].segments[place_index]
@@ -158,7 +142,7 @@ impl LineProbability {
                 }
             }
 
-            for color_case in self.color_cases.iter_mut() {
+            for color_case in &mut self.color_cases {
                 color_case.fill(0);
             }
 
@@ -173,7 +157,7 @@ impl LineProbability {
                 {
                     let product = match segment.block_states {
                         BlockStates::Open => segment.left_cases * segment.right_cases,
-                        _ => 0,
+                        BlockStates::Blocked(_) => 0,
                     };
 
                     for index in
@@ -192,7 +176,7 @@ impl LineProbability {
                 return false;
             }
 
-            for color_case in self.color_cases.iter_mut() {
+            for color_case in &mut self.color_cases {
                 color_case[0] = self.painting_count;
                 for paint_index in 1..color_case.len() {
                     color_case[0] -= color_case[paint_index];
@@ -0,0 +1,123 @@
+use super::{BranchHeuristic, LinePriority, SolveResult, SolveStats};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Settings of `solve_with`. The default solves without any limit, like `solve`.
+#[derive(Debug, Clone, Default)]
+pub struct SolveOptions {
+    /// Stop with `TimedOut` once this much time has passed since the start of the solve.
+    pub timeout: Option<Duration>,
+    /// Stop with `Cancelled` once this token is cancelled, e.g. from another thread.
+    pub cancellation: Option<CancellationToken>,
+    /// Stop with `BudgetExhausted` instead of solving more lines than this.
+    pub max_line_solves: Option<u128>,
+    /// Stop with `BudgetExhausted` instead of branching more times than this.
+    ///
+    /// In parallel, each branch checks the budgets against its own count of the work done
+    /// since the branching, so that the search may do more in total.
+    pub max_backtracks: Option<u128>,
+    /// Explore the two branches of a choice on separate threads, as long as the choice is
+    /// shallower than this depth. The default 0 keeps the whole search on the calling thread.
+    pub parallel_depth: usize,
+    /// How to choose the pixel and the color to branch on. `None` uses `ProbabilityHeuristic`.
+    pub branch_heuristic: Option<Arc<dyn BranchHeuristic>>,
+    /// In which order to solve the lines waiting in the queue. `None` uses `ProbabilityPriority`.
+    pub line_priority: Option<Arc<dyn LinePriority>>,
+    /// Before each branch, try every candidate color of every pixel once and keep what does not
+    /// depend on the choice.
+    ///
+    /// Each trial propagates the whole grid twice, which costs many line solves: on some puzzles
+    /// probing solves ten times as many lines and saves few backtracks, while on others it cuts
+    /// a deep search short. Off by default.
+    pub probing: bool,
+}
+
+/// A flag shared between the solver and its caller to stop a solve early.
+///
+/// The solver checks it between line solves and before each branch, so it stops shortly after
+/// `cancel` is called.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// The limits of a running solve, derived from its `SolveOptions`.
+#[derive(Debug, Default)]
+pub(super) struct Limits {
+    deadline: Option<Instant>,
+    cancellation: Option<CancellationToken>,
+    max_line_solves: Option<u128>,
+    max_backtracks: Option<u128>,
+}
+
+impl Limits {
+    pub(super) fn new(options: &SolveOptions) -> Self {
+        Self {
+            deadline: options.timeout.map(|timeout| Instant::now() + timeout),
+            cancellation: options.cancellation.clone(),
+            max_line_solves: options.max_line_solves,
+            max_backtracks: options.max_backtracks,
+        }
+    }
+
+    /// Returns the result to stop with if a limit prevents solving one more line.
+    pub(super) fn check_line_solve(&self, stats: &SolveStats) -> Result<(), SolveResult> {
+        self.check_interruption()?;
+        if self
+            .max_line_solves
+            .is_some_and(|max| stats.line_solves >= max)
+        {
+            return Err(SolveResult::BudgetExhausted);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the result to stop with if a limit prevents branching once more.
+    pub(super) fn check_branch(&self, stats: &SolveStats) -> Result<(), SolveResult> {
+        self.check_interruption()?;
+        if self
+            .max_backtracks
+            .is_some_and(|max| stats.backtracks >= max)
+        {
+            return Err(SolveResult::BudgetExhausted);
+        }
+
+        Ok(())
+    }
+
+    fn check_interruption(&self) -> Result<(), SolveResult> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(SolveResult::Cancelled);
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(SolveResult::TimedOut);
+        }
+
+        Ok(())
+    }
+}
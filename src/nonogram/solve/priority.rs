@@ -0,0 +1,123 @@
+use super::LineId;
+use std::fmt;
+
+/// A line of the first pass, which solves every line once before any pixel is known.
+#[derive(Debug, Clone, Copy)]
+pub struct InitialLine {
+    pub line_id: LineId,
+    /// The slack the clue leaves in the line: how far its runs can move,
+    /// as the number of leftmost positions of each run.
+    pub slack: usize,
+    /// The number of arrangements of the line before solving it.
+    pub arrangement_num: u128,
+    /// The position of the line in the puzzle, rows first.
+    pub sequence: u64,
+}
+
+/// A line to solve again because one of its pixels lost colors.
+#[derive(Debug, Clone, Copy)]
+pub struct LineChange<'a> {
+    pub line_id: LineId,
+    /// The 0-based index of the pixel in the line.
+    pub pixel_index: usize,
+    /// The ratio of the arrangements of the line that the pixel still allows.
+    pub kept_ratio: f64,
+    /// The number of lines queued before this one in the search, so that it orders the changes.
+    pub sequence: u64,
+    /// The possible colors of every pixel after the change.
+    pub(super) grid: &'a [Vec<usize>],
+}
+
+impl LineChange<'_> {
+    /// The number of pixels of the line that are still undetermined after the change.
+    /// It scans the line, so the policies that do not need it do not pay for it.
+    #[must_use]
+    pub fn count_undetermined(&self) -> usize {
+        let is_undetermined = |memo: usize| memo.count_ones() > 1;
+        match self.line_id {
+            LineId::Row(index) => self.grid[index]
+                .iter()
+                .filter(|&&memo| is_undetermined(memo))
+                .count(),
+            LineId::Column(index) => self
+                .grid
+                .iter()
+                .filter(|row| is_undetermined(row[index]))
+                .count(),
+        }
+    }
+}
+
+/// Orders the lines waiting to be solved. The line with the lowest priority is solved first.
+pub trait LinePriority: fmt::Debug + Send + Sync {
+    fn initial(&self, line: &InitialLine) -> f64;
+
+    /// Returns the new priority of the line, given its priority if it is already queued.
+    fn requeue(&self, queued: Option<f64>, change: &LineChange) -> f64;
+}
+
+/// Starts from the lines with the fewest arrangements, then solves first the lines whose
+/// arrangements were cut the most, summing the logarithms of the kept ratios. This is the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProbabilityPriority;
+
+impl LinePriority for ProbabilityPriority {
+    #[allow(clippy::cast_precision_loss)]
+    fn initial(&self, line: &InitialLine) -> f64 {
+        line.arrangement_num as f64
+    }
+
+    fn requeue(&self, queued: Option<f64>, change: &LineChange) -> f64 {
+        queued.unwrap_or_default() + change.kept_ratio.ln()
+    }
+}
+
+/// Solves the lines in the order they were queued, rows first in the first pass.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FifoPriority;
+
+impl LinePriority for FifoPriority {
+    #[allow(clippy::cast_precision_loss)]
+    fn initial(&self, line: &InitialLine) -> f64 {
+        line.sequence as f64
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn requeue(&self, queued: Option<f64>, change: &LineChange) -> f64 {
+        queued.unwrap_or(change.sequence as f64)
+    }
+}
+
+/// Solves first the lines with the most pixels changed since they were queued.
+/// The first pass goes in order, rows first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChangedCellsPriority;
+
+impl LinePriority for ChangedCellsPriority {
+    #[allow(clippy::cast_precision_loss)]
+    fn initial(&self, line: &InitialLine) -> f64 {
+        line.sequence as f64
+    }
+
+    fn requeue(&self, queued: Option<f64>, _change: &LineChange) -> f64 {
+        queued.unwrap_or_default() - 1.0
+    }
+}
+
+/// Solves first the lines with the least free space left: in the first pass, those whose clue
+/// leaves the least slack, and then those with the fewest pixels still undetermined.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FreeSpacePriority;
+
+impl LinePriority for FreeSpacePriority {
+    #[allow(clippy::cast_precision_loss)]
+    fn initial(&self, line: &InitialLine) -> f64 {
+        line.slack as f64
+    }
+
+    /// The latest change of a queued line counts, since it leaves the least free space.
+    #[allow(clippy::cast_precision_loss)]
+    fn requeue(&self, _queued: Option<f64>, change: &LineChange) -> f64 {
+        change.count_undetermined() as f64
+    }
+}
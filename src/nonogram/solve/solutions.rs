@@ -0,0 +1,86 @@
+use super::{
+    Arc, FxPriorityQueue, LayerSolver, LineId, Priority, Puzzle, Solution, SolveOptions,
+    SolveResources, SolveStats, Stopwatch,
+};
+
+/// A lazy iterator over the solutions of a puzzle, returned by `solutions`.
+///
+/// It explores the same branches as `solve` depth first, but yields every complete grid
+/// instead of merging the branches.
+#[derive(Debug)]
+pub struct Solutions<'a> {
+    colors: &'a [String],
+    stack: Vec<(LayerSolver<'a>, FxPriorityQueue<LineId, Priority>)>,
+    stats: SolveStats,
+}
+
+impl Solutions<'_> {
+    /// The counters accumulated by the search so far.
+    ///
+    /// The times only cover the work done inside `next`, not the time spent by the caller.
+    #[must_use]
+    pub fn get_stats(&self) -> SolveStats {
+        self.stats
+    }
+}
+
+impl Iterator for Solutions<'_> {
+    type Item = Solution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let stopwatch = Stopwatch::start();
+        let result = self.search();
+        stopwatch.stop(&mut self.stats);
+        result
+    }
+}
+
+impl Solutions<'_> {
+    fn search(&mut self) -> Option<Solution> {
+        while let Some((mut layer_solver, mut priority_queue)) = self.stack.pop() {
+            if layer_solver
+                .propagate(&mut priority_queue, &mut self.stats)
+                .is_err()
+            {
+                continue;
+            }
+
+            if let Some((pixel_id, color_index)) = layer_solver.find_branch_pixel() {
+                layer_solver.count_backtrack(&mut self.stats);
+                let colors1 = 1 << color_index;
+                let colors2 = layer_solver.get_memo(pixel_id) ^ colors1;
+
+                self.stack
+                    .push(layer_solver.new_child(pixel_id, colors2, colors1));
+                self.stack
+                    .push(layer_solver.new_child(pixel_id, colors1, colors2));
+            } else {
+                return Some(layer_solver.to_solution(self.colors));
+            }
+        }
+
+        None
+    }
+}
+
+/// Enumerates the solutions of the puzzle one by one.
+#[must_use]
+pub fn solutions(puzzle: &Puzzle) -> Solutions<'_> {
+    let stopwatch = Stopwatch::start();
+    let mut stats = SolveStats::default();
+    let mut layer_solver = LayerSolver::new_root(Arc::new(SolveResources::new(
+        puzzle,
+        &SolveOptions::default(),
+    )));
+    let stack = match layer_solver.init(&mut stats) {
+        Ok(priority_queue) => vec![(layer_solver, priority_queue)],
+        Err(_) => Vec::new(),
+    };
+    stopwatch.stop(&mut stats);
+
+    Solutions {
+        colors: &puzzle.colors,
+        stack,
+        stats,
+    }
+}
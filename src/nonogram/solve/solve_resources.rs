@@ -1,11 +1,17 @@
 use num_integer::binomial;
+use std::sync::Arc;
 
-use super::*;
+use super::{
+    heuristic::{BranchHeuristic, ProbabilityHeuristic},
+    options::{Limits, SolveOptions},
+    priority::{LinePriority, ProbabilityPriority},
+    LineClue, LineId, Puzzle,
+};
 
-fn calc_free(length: usize, line_clues: &Vec<LineClue>) -> Vec<usize> {
-    let mut result: Vec<usize> = Default::default();
+fn calc_free(length: usize, line_clues: &[LineClue]) -> Vec<usize> {
+    let mut result: Vec<usize> = Vec::new();
 
-    for line_clue in line_clues.iter() {
+    for line_clue in line_clues {
         let d_num = line_clue.len();
 
         if d_num == 0 {
@@ -38,10 +44,17 @@ pub struct SolveResources<'a> {
     pub uncertain_memo: usize,
     clues: &'a (Vec<LineClue>, Vec<LineClue>),
     free: (Vec<usize>, Vec<usize>),
+    pub limits: Limits,
+    /// The layers shallower than this explore their two branches in parallel.
+    pub parallel_depth: usize,
+    pub branch_heuristic: Arc<dyn BranchHeuristic>,
+    pub line_priority: Arc<dyn LinePriority>,
+    /// Whether to probe the candidates of branching before each branch.
+    pub probing: bool,
 }
 
 impl<'a> SolveResources<'a> {
-    pub fn new(puzzle: &'a Puzzle) -> Self {
+    pub fn new(puzzle: &'a Puzzle, options: &SolveOptions) -> Self {
         let height = puzzle.get_height();
         let width = puzzle.get_width();
         let color_num = puzzle.get_color_num();
@@ -57,6 +70,17 @@ impl<'a> SolveResources<'a> {
                 calc_free(width, &puzzle.clues.0),
                 calc_free(height, &puzzle.clues.1),
             ),
+            limits: Limits::new(options),
+            parallel_depth: options.parallel_depth,
+            branch_heuristic: options
+                .branch_heuristic
+                .clone()
+                .unwrap_or_else(|| Arc::new(ProbabilityHeuristic)),
+            line_priority: options
+                .line_priority
+                .clone()
+                .unwrap_or_else(|| Arc::new(ProbabilityPriority)),
+            probing: options.probing,
         }
     }
 
@@ -0,0 +1,70 @@
+use cpu_time::ProcessTime;
+use serde::{Serialize, Serializer};
+use std::time::{Duration, Instant};
+
+/// Counters and timings collected while solving.
+///
+/// It serializes to JSON with the times in seconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SolveStats {
+    pub line_solves: u128,
+    pub backtracks: u128,
+    /// The deepest nesting of branches reached by the search.
+    pub max_depth: usize,
+    /// The number of line probability tables that a layer built for itself
+    /// instead of sharing the ones of its parent.
+    pub probability_recomputations: u128,
+    #[serde(serialize_with = "serialize_secs")]
+    pub wall_time: Duration,
+    #[serde(serialize_with = "serialize_secs")]
+    pub cpu_time: Duration,
+}
+
+impl SolveStats {
+    /// Serializes the statistics into a single line of JSON.
+    ///
+    /// # Panics
+    ///
+    /// Never panics in practice, since every field is serializable.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("SolveStats is always serializable")
+    }
+}
+
+impl SolveStats {
+    /// Adds the work of two branches explored in parallel, which both started from these statistics.
+    pub(super) fn add_branches(&mut self, stats1: &SolveStats, stats2: &SolveStats) {
+        self.line_solves = stats1.line_solves + stats2.line_solves - self.line_solves;
+        self.backtracks = stats1.backtracks + stats2.backtracks - self.backtracks;
+        self.max_depth = stats1.max_depth.max(stats2.max_depth);
+        self.probability_recomputations = stats1.probability_recomputations
+            + stats2.probability_recomputations
+            - self.probability_recomputations;
+    }
+}
+
+fn serialize_secs<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+/// Measures the wall time and the CPU time of a part of the search.
+pub(super) struct Stopwatch {
+    start: Instant,
+    cpu_start: ProcessTime,
+}
+
+impl Stopwatch {
+    pub(super) fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            cpu_start: ProcessTime::now(),
+        }
+    }
+
+    /// Adds the time elapsed since `start` to the statistics.
+    pub(super) fn stop(&self, stats: &mut SolveStats) {
+        stats.wall_time += self.start.elapsed();
+        stats.cpu_time += self.cpu_start.elapsed();
+    }
+}
@@ -0,0 +1,394 @@
+use super::{LineClue, LineId, Puzzle};
+use std::error::Error;
+use std::fmt;
+
+/// A reason why a puzzle cannot be solved as it is written.
+///
+/// Description indices are 0-based, but displayed 1-based like `LineId`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// There is no color, not even the background one.
+    NoColors,
+    /// The puzzle has no rows or no columns.
+    EmptyGrid { height: usize, width: usize },
+    /// A row of the grid given to `Puzzle::from_grid` does not have the length of the first one.
+    RaggedGrid {
+        row_index: usize,
+        length: usize,
+        width: usize,
+    },
+    /// The colors do not fit in the bitmask of a pixel.
+    TooManyColors { color_num: usize },
+    /// A description refers to a color that is not in `colors`.
+    UnknownColor {
+        line_id: LineId,
+        description_index: usize,
+        color_index: usize,
+    },
+    /// A description is painted with the color 0, which is the background.
+    BackgroundColor {
+        line_id: LineId,
+        description_index: usize,
+    },
+    /// A description has no cells.
+    EmptyDescription {
+        line_id: LineId,
+        description_index: usize,
+    },
+    /// The descriptions of a line, with the blanks required between them, do not fit in it.
+    LineTooShort {
+        line_id: LineId,
+        min_length: usize,
+        length: usize,
+    },
+    /// The rows and the columns paint a different number of cells with a color.
+    ColorTotalMismatch {
+        color_index: usize,
+        color_name: String,
+        row_total: usize,
+        column_total: usize,
+    },
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::NoColors => write!(f, "The puzzle has no colors, not even the background"),
+            PuzzleError::EmptyGrid { height, width } => write!(
+                f,
+                "The puzzle has {height} rows and {width} columns, but needs at least one of each"
+            ),
+            PuzzleError::RaggedGrid {
+                row_index,
+                length,
+                width,
+            } => write!(
+                f,
+                "Row {} of the grid has {length} pixels, but row 1 has {width}",
+                row_index + 1
+            ),
+            PuzzleError::TooManyColors { color_num } => write!(
+                f,
+                "The puzzle has {color_num} colors, but at most {} are supported",
+                usize::BITS - 1
+            ),
+            PuzzleError::UnknownColor {
+                line_id,
+                description_index,
+                color_index,
+            } => write!(
+                f,
+                "Description {} of {line_id} has the color index {color_index}, which is not defined",
+                description_index + 1
+            ),
+            PuzzleError::BackgroundColor {
+                line_id,
+                description_index,
+            } => write!(
+                f,
+                "Description {} of {line_id} has the background color",
+                description_index + 1
+            ),
+            PuzzleError::EmptyDescription {
+                line_id,
+                description_index,
+            } => write!(
+                f,
+                "Description {} of {line_id} has the number 0",
+                description_index + 1
+            ),
+            PuzzleError::LineTooShort {
+                line_id,
+                min_length,
+                length,
+            } => write!(
+                f,
+                "The descriptions of {line_id} need {min_length} cells, but the line has only {length}"
+            ),
+            PuzzleError::ColorTotalMismatch {
+                color_name,
+                row_total,
+                column_total,
+                ..
+            } => write!(
+                f,
+                "The rows have {row_total} cells of the color \"{color_name}\", but the columns have {column_total}"
+            ),
+        }
+    }
+}
+
+impl Error for PuzzleError {}
+
+impl Puzzle {
+    /// Checks that the puzzle is well-formed, so that the solver can work on it.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first problem found, naming the offending line and description.
+    pub fn validate(&self) -> Result<(), PuzzleError> {
+        let color_num = self.colors.len();
+        if color_num == 0 {
+            return Err(PuzzleError::NoColors);
+        }
+        if color_num >= usize::BITS as usize {
+            return Err(PuzzleError::TooManyColors { color_num });
+        }
+
+        let height = self.clues.0.len();
+        let width = self.clues.1.len();
+        if height == 0 || width == 0 {
+            return Err(PuzzleError::EmptyGrid { height, width });
+        }
+        let rows = self.clues.0.iter().enumerate();
+        let columns = self.clues.1.iter().enumerate();
+        for (line_id, line_clue, length) in rows
+            .map(|(index, line_clue)| (LineId::Row(index), line_clue, width))
+            .chain(columns.map(|(index, line_clue)| (LineId::Column(index), line_clue, height)))
+        {
+            self.validate_line(line_id, line_clue, length)?;
+        }
+
+        self.validate_color_totals()
+    }
+
+    /// Checks that every color paints as many cells according to the rows as to the columns.
+    fn validate_color_totals(&self) -> Result<(), PuzzleError> {
+        let row_totals = self.count_color_totals(&self.clues.0);
+        let column_totals = self.count_color_totals(&self.clues.1);
+
+        for (color_index, (&row_total, &column_total)) in
+            row_totals.iter().zip(&column_totals).enumerate()
+        {
+            if row_total != column_total {
+                return Err(PuzzleError::ColorTotalMismatch {
+                    color_index,
+                    color_name: self.colors[color_index].clone(),
+                    row_total,
+                    column_total,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn count_color_totals(&self, line_clues: &[LineClue]) -> Vec<usize> {
+        let mut totals = vec![0; self.colors.len()];
+        for description in line_clues.iter().flatten() {
+            totals[description.color_index] += description.number;
+        }
+
+        totals
+    }
+
+    fn validate_line(
+        &self,
+        line_id: LineId,
+        line_clue: &LineClue,
+        length: usize,
+    ) -> Result<(), PuzzleError> {
+        for (description_index, description) in line_clue.iter().enumerate() {
+            if description.color_index >= self.colors.len() {
+                return Err(PuzzleError::UnknownColor {
+                    line_id,
+                    description_index,
+                    color_index: description.color_index,
+                });
+            }
+            if description.color_index == 0 {
+                return Err(PuzzleError::BackgroundColor {
+                    line_id,
+                    description_index,
+                });
+            }
+            if description.number == 0 {
+                return Err(PuzzleError::EmptyDescription {
+                    line_id,
+                    description_index,
+                });
+            }
+        }
+
+        let min_length = min_length(line_clue);
+        if min_length > length {
+            return Err(PuzzleError::LineTooShort {
+                line_id,
+                min_length,
+                length,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of cells the descriptions occupy when packed to one end of the line.
+///
+/// It saturates instead of overflowing on absurd numbers, which then cannot fit in any line.
+fn min_length(line_clue: &LineClue) -> usize {
+    let separators = line_clue
+        .windows(2)
+        .filter(|pair| pair[0].color_index == pair[1].color_index)
+        .count();
+
+    line_clue.iter().fold(separators, |sum, description| {
+        sum.saturating_add(description.number)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Description;
+
+    fn black_and_white(rows: Vec<LineClue>, columns: Vec<LineClue>) -> Result<Puzzle, PuzzleError> {
+        Puzzle::new(
+            vec!["white".to_string(), "black".to_string()],
+            rows,
+            columns,
+        )
+    }
+
+    fn black(number: usize) -> Description {
+        Description::new(1, number)
+    }
+
+    #[test]
+    fn puzzle_without_colors_is_rejected() {
+        let error = Puzzle::new(Vec::new(), vec![Vec::new()], vec![Vec::new()]).unwrap_err();
+        assert_eq!(error, PuzzleError::NoColors);
+    }
+
+    #[test]
+    fn puzzle_with_too_many_colors_is_rejected() {
+        let colors = (0..usize::BITS).map(|index| index.to_string()).collect();
+        let error = Puzzle::new(colors, vec![Vec::new()], vec![Vec::new()]).unwrap_err();
+        assert_eq!(
+            error,
+            PuzzleError::TooManyColors {
+                color_num: usize::BITS as usize
+            }
+        );
+    }
+
+    #[test]
+    fn ragged_grid_is_rejected() {
+        let colors = vec!["white".to_string(), "black".to_string()];
+        let error = Puzzle::from_grid(colors, &[vec![1, 0], vec![0, 1], vec![1]]).unwrap_err();
+        assert_eq!(
+            error,
+            PuzzleError::RaggedGrid {
+                row_index: 2,
+                length: 1,
+                width: 2
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_color_is_rejected() {
+        let error = black_and_white(
+            vec![vec![black(1), Description::new(2, 1)]],
+            vec![Vec::new(); 3],
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            PuzzleError::UnknownColor {
+                line_id: LineId::Row(0),
+                description_index: 1,
+                color_index: 2
+            }
+        );
+    }
+
+    #[test]
+    fn background_description_is_rejected() {
+        let error =
+            black_and_white(vec![Vec::new()], vec![vec![Description::new(0, 1)]]).unwrap_err();
+        assert_eq!(
+            error,
+            PuzzleError::BackgroundColor {
+                line_id: LineId::Column(0),
+                description_index: 0
+            }
+        );
+    }
+
+    #[test]
+    fn empty_description_is_rejected() {
+        let error = black_and_white(vec![vec![black(0)]], vec![Vec::new()]).unwrap_err();
+        assert_eq!(
+            error,
+            PuzzleError::EmptyDescription {
+                line_id: LineId::Row(0),
+                description_index: 0
+            }
+        );
+    }
+
+    #[test]
+    fn line_too_short_counts_the_separators() {
+        // Two runs of the same color need a blank between them: 1 + 1 + 1 > 2.
+        let error =
+            black_and_white(vec![vec![black(1), black(1)]], vec![vec![black(1)]; 2]).unwrap_err();
+        assert_eq!(
+            error,
+            PuzzleError::LineTooShort {
+                line_id: LineId::Row(0),
+                min_length: 3,
+                length: 2
+            }
+        );
+    }
+
+    #[test]
+    fn absurd_numbers_saturate_the_min_length() {
+        let error = black_and_white(
+            vec![vec![black(usize::MAX), black(usize::MAX)]],
+            vec![Vec::new()],
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            PuzzleError::LineTooShort {
+                line_id: LineId::Row(0),
+                min_length: usize::MAX,
+                length: 1
+            }
+        );
+    }
+
+    #[test]
+    fn puzzle_without_rows_or_columns_is_rejected() {
+        let puzzle: Result<Puzzle, _> = r#"{"colors":["w","b"],"clues":[[[]],[]]}"#.parse();
+        let error = puzzle.unwrap_err().downcast::<PuzzleError>().unwrap();
+        assert_eq!(
+            error,
+            PuzzleError::EmptyGrid {
+                height: 1,
+                width: 0
+            }
+        );
+    }
+
+    #[test]
+    fn color_totals_must_agree() {
+        let colors = vec!["white".to_string(), "black".to_string(), "red".to_string()];
+        let rows = vec![vec![black(1), Description::new(2, 1)]];
+        let columns = vec![vec![black(1)], Vec::new(), Vec::new()];
+
+        let error = Puzzle::new(colors, rows, columns).unwrap_err();
+        assert_eq!(
+            error,
+            PuzzleError::ColorTotalMismatch {
+                color_index: 2,
+                color_name: "red".to_string(),
+                row_total: 1,
+                column_total: 0
+            }
+        );
+        assert!(error.to_string().contains("\"red\""), "{error}");
+    }
+}
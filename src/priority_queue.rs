@@ -1,9 +1,7 @@
 use fxhash::FxBuildHasher;
-use num_traits::Zero;
 use std::{
     collections::HashMap,
     hash::{BuildHasher, Hash, RandomState},
-    ops::AddAssign,
 };
 
 macro_rules! parent {
@@ -28,7 +26,7 @@ macro_rules! right_child {
     };
 }
 
-/// 'PriorityQueue' is a data structure that uses a combination of a hashmap and a binary heap.
+/// `PriorityQueue` is a data structure that uses a combination of a hashmap and a binary heap.
 /// You can reference the value associated with a key, and by popping from the heap, you can access the key at the top.
 /// Additionally, the heap is implemented as a min-heap.
 #[derive(Debug)]
@@ -42,8 +40,8 @@ pub type FxPriorityQueue<K, P> = PriorityQueue<K, P, FxBuildHasher>;
 impl<K, P, S: Default> Default for PriorityQueue<K, P, S> {
     fn default() -> Self {
         Self {
-            heap: Default::default(),
-            map: Default::default(),
+            heap: Vec::default(),
+            map: HashMap::default(),
         }
     }
 }
@@ -55,15 +53,15 @@ where
     S: BuildHasher + Default,
 {
     pub fn new() -> Self {
-        Default::default()
+        Self::default()
     }
 
     pub fn new_heapify(vec: Vec<(K, P)>) -> Self {
         let length = vec.len();
-        let first_index = if length == 0 { 0 } else { length / 2 - 1 };
+        let first_index = (length / 2).saturating_sub(1);
         let mut value = Self {
             heap: vec,
-            map: Default::default(),
+            map: HashMap::default(),
         };
 
         for index in (0..=first_index).rev() {
@@ -96,6 +94,27 @@ where
         result
     }
 
+    pub fn get(&self, key: &K) -> Option<&P> {
+        self.map.get(key).map(|&index| &self.heap[index].1)
+    }
+
+    pub fn set_or_insert(&mut self, key: K, priority: P) {
+        if let Some(&index) = self.map.get(&key) {
+            let is_lower = priority < self.heap[index].1;
+            self.heap[index].1 = priority;
+            if is_lower {
+                self.sift_up(index);
+            } else {
+                self.sift_down(index);
+            }
+        } else {
+            let index = self.heap.len();
+            self.map.insert(key.clone(), index);
+            self.heap.push((key, priority));
+            self.sift_up(index);
+        }
+    }
+
     fn partial_heapify(&mut self, length: usize, mut index: usize) {
         loop {
             let mut min_node = index;
@@ -121,16 +140,11 @@ where
     fn sift_up(&mut self, mut index: usize) -> usize {
         loop {
             match parent!(index) {
-                Some(parent) => {
-                    if self.heap[index].1 < self.heap[parent].1 {
-                        self.swap_node(index, parent);
-                        index = parent;
-                    } else {
-                        *(self.map.get_mut(&self.heap[index].0).unwrap()) = index;
-                        break index;
-                    }
+                Some(parent) if self.heap[index].1 < self.heap[parent].1 => {
+                    self.swap_node(index, parent);
+                    index = parent;
                 }
-                None => {
+                _ => {
                     *(self.map.get_mut(&self.heap[index].0).unwrap()) = index;
                     break index;
                 }
@@ -167,28 +181,36 @@ where
     }
 }
 
-impl<K, P, S> PriorityQueue<K, P, S>
-where
-    K: Eq + Hash + Clone,
-    P: PartialOrd + AddAssign + Zero + Copy,
-    S: BuildHasher + Default,
-{
-    pub fn add_or_insert(&mut self, key: K, priority: P) {
-        match self.map.get(&key) {
-            Some(index) => {
-                self.heap[*index].1 += priority;
-                if priority < P::zero() {
-                    self.sift_up(*index);
-                } else {
-                    self.sift_down(*index);
-                }
-            }
-            None => {
-                let index = self.heap.len();
-                self.map.insert(key.clone(), index);
-                self.heap.push((key, priority));
-                self.sift_up(index);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pops_in_priority_order() {
+        for length in 0..6 {
+            let vec = (0..length).map(|key| (key, (key * 7 + 3) % 5)).collect();
+            let mut priority_queue: FxPriorityQueue<usize, usize> = PriorityQueue::new_heapify(vec);
+
+            let mut priorities = Vec::new();
+            while let Some((_, priority)) = priority_queue.pop() {
+                priorities.push(priority);
             }
+            assert_eq!(priorities.len(), length);
+            assert!(priorities.is_sorted());
         }
     }
+
+    #[test]
+    fn set_or_insert_moves_the_key() {
+        let mut priority_queue: FxPriorityQueue<char, i32> =
+            PriorityQueue::new_heapify(vec![('a', 1), ('b', 2), ('c', 3)]);
+        priority_queue.set_or_insert('c', 0);
+        priority_queue.set_or_insert('a', 4);
+        priority_queue.set_or_insert('d', 3);
+
+        assert_eq!(priority_queue.get(&'a'), Some(&4));
+        let keys: Vec<char> =
+            std::iter::from_fn(|| priority_queue.pop().map(|(key, _)| key)).collect();
+        assert_eq!(keys, ['c', 'b', 'd', 'a']);
+    }
 }